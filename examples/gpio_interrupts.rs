@@ -0,0 +1,75 @@
+//! Toggles an LED on every press of the user button
+//! using the external interrupt of the button pin.
+//! Target board: STM32F3DISCOVERY
+
+#![no_main]
+#![no_std]
+
+use core::cell::RefCell;
+
+use panic_semihosting as _;
+
+use cortex_m::interrupt::Mutex;
+use cortex_m::peripheral::NVIC;
+use cortex_m_rt::entry;
+
+use stm32f3xx_hal as hal;
+
+use hal::gpio::{gpioa, gpioe, Edge, Input, Output, PullDown, PushPull};
+use hal::interrupt;
+use hal::pac;
+use hal::prelude::*;
+
+type LedPin = gpioe::PE9<Output<PushPull>>;
+static LED: Mutex<RefCell<Option<LedPin>>> = Mutex::new(RefCell::new(None));
+
+type ButtonPin = gpioa::PA0<Input<PullDown>>;
+static BUTTON: Mutex<RefCell<Option<ButtonPin>>> = Mutex::new(RefCell::new(None));
+
+#[entry]
+fn main() -> ! {
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut rcc = dp.RCC.constrain();
+    let mut syscfg = dp.SYSCFG.constrain(&mut rcc.apb2);
+    let mut exti = dp.EXTI;
+
+    let mut gpioe = dp.GPIOE.split(&mut rcc.ahb);
+    let mut led = gpioe
+        .pe9
+        .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper);
+    led.set_low().unwrap();
+
+    let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
+    let mut button = gpioa
+        .pa0
+        .into_pull_down_input(&mut gpioa.moder, &mut gpioa.pupdr);
+    button.make_interrupt_source(&mut syscfg);
+    button.trigger_on_edge(&mut exti, Edge::Rising);
+    button.enable_interrupt(&mut exti);
+
+    cortex_m::interrupt::free(|cs| {
+        LED.borrow(cs).replace(Some(led));
+        BUTTON.borrow(cs).replace(Some(button));
+    });
+
+    // NOTE(unsafe) the interrupt handler only accesses the shared state
+    // through the critical sections above
+    unsafe { NVIC::unmask(interrupt::EXTI0) };
+
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
+
+#[interrupt]
+fn EXTI0() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(led) = LED.borrow(cs).borrow_mut().as_mut() {
+            led.toggle().unwrap();
+        }
+        if let Some(button) = BUTTON.borrow(cs).borrow_mut().as_mut() {
+            button.clear_interrupt_pending_bit();
+        }
+    });
+}
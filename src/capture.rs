@@ -0,0 +1,523 @@
+/*!
+  # Input capture
+
+  Timer channels can not only generate PWM signals (see [`pwm`](crate::pwm)),
+  but also capture the counter value of the timer on an edge of their input
+  pin. This can be used to measure the time between edges, or, with the
+  PWM input mode, the period and duty cycle of a signal.
+
+  ## Creating the (unconfigured) channels
+
+  Similar to the PWM channels, we first convert the timer peripheral into a
+  set of channels. The timer counts with the given tick frequency, which
+  determines the resolution of the captured values.
+
+  ```
+    // (Other imports omitted)
+    use stm32f3xx-hal::capture::tim3;
+
+    let dp = stm32f303::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let mut rcc = dp.RCC.constrain();
+    let clocks = rcc.cfgr.freeze(&mut flash.acr);
+
+    // Count with 1 MHz, so every tick is one microsecond
+    let (ch1_no_pins, _, _, _) = tim3(dp.TIM3, 1.mhz(), &clocks);
+  ```
+
+  ## Connecting a pin and capturing
+
+  Every channel takes exactly one input pin. Afterwards the edge polarity,
+  the input filter and the prescaler can be configured.
+
+  ```
+    let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
+    let pa6 = gpioa.pa6.into_af2(&mut gpioa.moder, &mut gpioa.afrl);
+
+    let mut ch1 = ch1_no_pins.input_from(pa6);
+    ch1.set_polarity(Polarity::Falling);
+    ch1.enable();
+
+    let ticks = block!(ch1.capture()).unwrap();
+  ```
+
+  ## PWM input mode
+
+  Channel 1 and channel 2 of a timer can be combined to measure the period
+  and duty cycle of the signal at the pin of channel 1.
+
+  ```
+    let (ch1_no_pins, ch2_no_pins, _, _) = tim3(dp.TIM3, 1.mhz(), &clocks);
+    let pwm_input = ch1_no_pins.input_from(pa6).into_pwm_input(ch2_no_pins);
+
+    let frequency = pwm_input.frequency();
+    let duty_cycle = pwm_input.duty_cycle();
+    let high_ticks = pwm_input.duty_ticks();
+  ```
+*/
+
+use core::marker::PhantomData;
+
+use crate::{
+    pac::RCC,
+    pwm::{ChannelPin, NoPins, WithPins},
+    rcc::Clocks,
+    time::Hertz,
+    timer::{prescaler, Timeout},
+};
+
+/// Signal edge(s) on which the counter is captured
+pub enum Polarity {
+    /// Capture on the rising edge
+    Rising,
+    /// Capture on the falling edge
+    Falling,
+    /// Capture on both edges
+    Both,
+}
+
+/// Number of edges, after which a capture is done
+pub enum Prescaler {
+    /// Capture on every edge
+    Div1,
+    /// Capture on every 2nd edge
+    Div2,
+    /// Capture on every 4th edge
+    Div4,
+    /// Capture on every 8th edge
+    Div8,
+}
+
+/// Input capture error
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A value was captured, before the previous value was read
+    Overcapture,
+}
+
+/// Representation of an input capture channel of an abritary timer channel,
+/// that also holds a type state for whether or not this channel
+/// is connected to a pin yet.
+///
+/// If there is no pin supplied, it cannot be used.
+pub struct CaptureChannel<X, T> {
+    clock: Hertz,
+    timx_chy: PhantomData<X>,
+    pin_status: PhantomData<T>,
+}
+
+impl<X, T> CaptureChannel<X, T> {
+    /// Returns the frequency with which the timer counts
+    pub fn tick_frequency(&self) -> Hertz {
+        self.clock
+    }
+}
+
+/// Period and duty cycle measurement of a signal,
+/// using channel 1 and channel 2 of a timer
+///
+/// Channel 1 captures the period on the rising edge of the signal and
+/// channel 2 captures the high time on the falling edge of the signal.
+/// The counter is reset on each rising edge.
+pub struct PwmInput<TIM> {
+    clock: Hertz,
+    tim: PhantomData<TIM>,
+}
+
+macro_rules! capture_timer {
+    ($timx:ident, $TIMx:ident, $res:ty, $apbxenr:ident, $apbxrstr:ident, $timclkz:ident, $timxrst:ident, $timxen:ident, [$($TIMx_CHy:ident: $y:tt),+]) => {
+        /// Create one or more input capture channels from a TIM Peripheral
+        ///
+        /// This function requires the frequency with which the timer counts
+        /// and the frozen clock configuration. The tick frequency determines
+        /// the resolution of the captured values.
+        #[allow(unused_parens)]
+        pub fn $timx<F: Into<Hertz>>(tim: crate::pac::$TIMx, freq: F, clocks: &Clocks) -> ($(CaptureChannel<crate::pwm::$TIMx_CHy, NoPins>),+) {
+            // Power the timer and reset it to ensure a clean state
+            // We use unsafe here to abstract away this implementation detail
+            // Justification: It is safe because only scopes with mutable references
+            // to TIMx should ever modify this bit.
+            unsafe {
+                (*RCC::ptr()).$apbxenr.modify(|_, w| w.$timxen().set_bit());
+                (*RCC::ptr()).$apbxrstr.modify(|_, w| w.$timxrst().set_bit());
+                (*RCC::ptr()).$apbxrstr.modify(|_, w| w.$timxrst().clear_bit());
+            }
+
+            // Let the counter run through the whole range
+            // NOTE(write): uses all bits of this register.
+            tim.arr.write(|w| unsafe { w.bits(u32::from(<$res>::max_value())) });
+
+            let clock_freq = clocks.$timclkz();
+            let ticks = Timeout::Frequency(freq.into()).ticks(clock_freq);
+            let psc = crate::expect!(prescaler(ticks, 1).ok(), "Unreachable tick frequency");
+            // NOTE(write): uses all bits of this register.
+            #[allow(unused_unsafe)]
+            tim.psc.write(|w| unsafe { w.psc().bits(psc) });
+
+            // Make the settings reload immediately
+            // NOTE(write): write to a state-less register.
+            tim.egr.write(|w| w.ug().set_bit());
+
+            // Enable the Timer
+            tim.cr1.modify(|_, w| w.cen().set_bit());
+
+            let clock = Hertz(clock_freq.0 / (u32::from(psc) + 1));
+            ($(CaptureChannel::<crate::pwm::$TIMx_CHy, NoPins> {
+                clock,
+                timx_chy: PhantomData,
+                pin_status: PhantomData,
+            }),+)
+        }
+
+        $(
+            capture_channel!($TIMx, $TIMx_CHy, $y);
+        )+
+    };
+}
+
+// CCMRx_INPUT register and bit offset of the channel y
+macro_rules! ccmr_input {
+    ($TIMx:ident, 1) => {
+        (&(*crate::pac::$TIMx::ptr()).ccmr1_input(), 0)
+    };
+    ($TIMx:ident, 2) => {
+        (&(*crate::pac::$TIMx::ptr()).ccmr1_input(), 8)
+    };
+    ($TIMx:ident, 3) => {
+        (&(*crate::pac::$TIMx::ptr()).ccmr2_input(), 0)
+    };
+    ($TIMx:ident, 4) => {
+        (&(*crate::pac::$TIMx::ptr()).ccmr2_input(), 8)
+    };
+}
+
+macro_rules! ccr {
+    ($TIMx:ident, 1) => {
+        (*crate::pac::$TIMx::ptr()).ccr1
+    };
+    ($TIMx:ident, 2) => {
+        (*crate::pac::$TIMx::ptr()).ccr2
+    };
+    ($TIMx:ident, 3) => {
+        (*crate::pac::$TIMx::ptr()).ccr3
+    };
+    ($TIMx:ident, 4) => {
+        (*crate::pac::$TIMx::ptr()).ccr4
+    };
+}
+
+macro_rules! capture_channel {
+    ($TIMx:ident, $TIMx_CHy:ident, $y:tt) => {
+        impl CaptureChannel<crate::pwm::$TIMx_CHy, NoPins> {
+            /// Capture the input of a specific pin with a channel, that
+            /// does not yet have a pin.
+            ///
+            /// The pin is consumed and cannot be returned.
+            pub fn input_from<P>(self, _p: P) -> CaptureChannel<crate::pwm::$TIMx_CHy, WithPins>
+            where
+                P: ChannelPin<crate::pwm::$TIMx_CHy>,
+            {
+                // NOTE(unsafe): Only the bits of this channel are modified.
+                unsafe {
+                    let (ccmr, offset) = ccmr_input!($TIMx, $y);
+                    // Map the input of this channel to its own pin (TIy)
+                    ccmr.modify(|r, w| w.bits((r.bits() & !(0xff << offset)) | (0b01 << offset)));
+                }
+                CaptureChannel {
+                    clock: self.clock,
+                    timx_chy: PhantomData,
+                    pin_status: PhantomData,
+                }
+            }
+        }
+
+        impl CaptureChannel<crate::pwm::$TIMx_CHy, WithPins> {
+            /// Enables the capture of this channel
+            pub fn enable(&mut self) {
+                // NOTE(unsafe): Only the bits of this channel are modified.
+                unsafe {
+                    (*crate::pac::$TIMx::ptr())
+                        .ccer
+                        .modify(|r, w| w.bits(r.bits() | (1 << (4 * ($y - 1)))));
+                }
+            }
+
+            /// Disables the capture of this channel
+            pub fn disable(&mut self) {
+                // NOTE(unsafe): Only the bits of this channel are modified.
+                unsafe {
+                    (*crate::pac::$TIMx::ptr())
+                        .ccer
+                        .modify(|r, w| w.bits(r.bits() & !(1 << (4 * ($y - 1)))));
+                }
+            }
+
+            /// Selects the edge(s) of the input signal, on which the counter is captured
+            pub fn set_polarity(&mut self, polarity: Polarity) {
+                // CCyP and CCyNP bits
+                let bits = match polarity {
+                    Polarity::Rising => 0b0000,
+                    Polarity::Falling => 0b0010,
+                    Polarity::Both => 0b1010,
+                };
+                let offset = 4 * ($y - 1);
+                // NOTE(unsafe): Only the bits of this channel are modified.
+                unsafe {
+                    (*crate::pac::$TIMx::ptr())
+                        .ccer
+                        .modify(|r, w| w.bits((r.bits() & !(0b1010 << offset)) | (bits << offset)));
+                }
+            }
+
+            /// Sets the digital filter of the input signal
+            ///
+            /// The filter value selects the sampling frequency and the number of
+            /// consecutive samples needed to validate an edge. `0` disables the
+            /// filter and `15` is the strongest filter. See the reference manual
+            /// (ICxF bits of the TIMx_CCMRx register) for the exact meaning of the values.
+            ///
+            /// # Panics
+            ///
+            /// Panics, if `filter` is greater than `15`.
+            pub fn set_filter(&mut self, filter: u8) {
+                crate::assert!(filter <= 0b1111);
+                // NOTE(unsafe): Only the bits of this channel are modified.
+                unsafe {
+                    let (ccmr, offset) = ccmr_input!($TIMx, $y);
+                    ccmr.modify(|r, w| {
+                        w.bits(
+                            (r.bits() & !(0b1111 << (offset + 4)))
+                                | (u32::from(filter) << (offset + 4)),
+                        )
+                    });
+                }
+            }
+
+            /// Sets the number of edges of the input signal, after which a capture is done
+            pub fn set_prescaler(&mut self, prescaler: Prescaler) {
+                let bits = match prescaler {
+                    Prescaler::Div1 => 0b00,
+                    Prescaler::Div2 => 0b01,
+                    Prescaler::Div4 => 0b10,
+                    Prescaler::Div8 => 0b11,
+                };
+                // NOTE(unsafe): Only the bits of this channel are modified.
+                unsafe {
+                    let (ccmr, offset) = ccmr_input!($TIMx, $y);
+                    ccmr.modify(|r, w| {
+                        w.bits((r.bits() & !(0b11 << (offset + 2))) | (bits << (offset + 2)))
+                    });
+                }
+            }
+
+            /// Returns the last captured counter value
+            pub fn get_capture(&self) -> u32 {
+                // NOTE(unsafe) atomic read with no side effects
+                unsafe { ccr!($TIMx, $y).read().bits() }
+            }
+
+            /// Waits for the next capture and returns the captured counter value
+            ///
+            /// Returns [`Error::Overcapture`], if more than one value was
+            /// captured since the last call.
+            pub fn capture(&mut self) -> nb::Result<u32, Error> {
+                // NOTE(unsafe) atomic read with no side effects
+                let sr = unsafe { (*crate::pac::$TIMx::ptr()).sr.read().bits() };
+                if sr & (1 << $y) == 0 {
+                    return Err(nb::Error::WouldBlock);
+                }
+                // Reading the value clears the capture flag
+                let value = self.get_capture();
+                if sr & (1 << ($y + 8)) != 0 {
+                    // NOTE(unsafe, write): The flags are cleared by writing 0,
+                    // writing 1 has no effect.
+                    unsafe {
+                        (*crate::pac::$TIMx::ptr())
+                            .sr
+                            .write(|w| w.bits(!(1 << ($y + 8))));
+                    }
+                    return Err(nb::Error::Other(Error::Overcapture));
+                }
+                Ok(value)
+            }
+        }
+    };
+}
+
+macro_rules! pwm_input {
+    ($TIMx:ident, $TIMx_CH1:ident, $TIMx_CH2:ident) => {
+        impl CaptureChannel<crate::pwm::$TIMx_CH1, WithPins> {
+            /// Combines channel 1 and channel 2 to measure the period and the
+            /// duty cycle of the signal at the pin of channel 1.
+            ///
+            /// The counter is reset on every rising edge of the signal.
+            pub fn into_pwm_input(
+                self,
+                _ch2: CaptureChannel<crate::pwm::$TIMx_CH2, NoPins>,
+            ) -> PwmInput<crate::pac::$TIMx> {
+                // NOTE(unsafe): The timer is owned by the channels, which are consumed.
+                unsafe {
+                    let tim = &*crate::pac::$TIMx::ptr();
+                    // Map channel 1 to TI1 and channel 2 also to TI1
+                    tim.ccmr1_input()
+                        .modify(|r, w| w.bits((r.bits() & !0xffff) | (0b10 << 8) | 0b01));
+                    // Capture channel 1 on the rising edge and channel 2
+                    // on the falling edge
+                    tim.ccer
+                        .modify(|r, w| w.bits((r.bits() & !0xff) | 0b0011_0001));
+                    // Trigger on TI1FP1 (TS = 0b101) and reset the counter
+                    // on the trigger (SMS = 0b100)
+                    tim.smcr
+                        .modify(|r, w| w.bits((r.bits() & !0x1_0077) | (0b101 << 4) | 0b100));
+                }
+                PwmInput {
+                    clock: self.clock,
+                    tim: PhantomData,
+                }
+            }
+        }
+
+        impl PwmInput<crate::pac::$TIMx> {
+            /// Returns the frequency with which the timer counts
+            pub fn tick_frequency(&self) -> Hertz {
+                self.clock
+            }
+
+            /// Returns the period of the signal in ticks
+            pub fn period_ticks(&self) -> u32 {
+                // NOTE(unsafe) atomic read with no side effects
+                unsafe { (*crate::pac::$TIMx::ptr()).ccr1.read().bits() }
+            }
+
+            /// Returns the high time of the signal in ticks
+            pub fn duty_ticks(&self) -> u32 {
+                // NOTE(unsafe) atomic read with no side effects
+                unsafe { (*crate::pac::$TIMx::ptr()).ccr2.read().bits() }
+            }
+
+            /// Returns the frequency of the signal
+            ///
+            /// Returns `None`, if no period was measured yet.
+            pub fn frequency(&self) -> Option<Hertz> {
+                match self.period_ticks() {
+                    0 => None,
+                    period => Some(Hertz(self.clock.0 / period)),
+                }
+            }
+
+            /// Returns the duty cycle of the signal, the ratio of the high time
+            /// to the period between `0.0` and `1.0`
+            ///
+            /// Returns `None`, if no period was measured yet.
+            pub fn duty_cycle(&self) -> Option<f32> {
+                match self.period_ticks() {
+                    0 => None,
+                    period => Some(self.duty_ticks() as f32 / period as f32),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f334",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+capture_timer!(tim1, TIM1, u16, apb2enr, apb2rstr, timclk2, tim1rst, tim1en, [TIM1_CH1: 1, TIM1_CH2: 2, TIM1_CH3: 3, TIM1_CH4: 4]);
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f334",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+pwm_input!(TIM1, TIM1_CH1, TIM1_CH2);
+
+capture_timer!(tim2, TIM2, u32, apb1enr, apb1rstr, timclk1, tim2rst, tim2en, [TIM2_CH1: 1, TIM2_CH2: 2, TIM2_CH3: 3, TIM2_CH4: 4]);
+pwm_input!(TIM2, TIM2_CH1, TIM2_CH2);
+
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f334",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+capture_timer!(tim3, TIM3, u16, apb1enr, apb1rstr, timclk1, tim3rst, tim3en, [TIM3_CH1: 1, TIM3_CH2: 2, TIM3_CH3: 3, TIM3_CH4: 4]);
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f334",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+pwm_input!(TIM3, TIM3_CH1, TIM3_CH2);
+
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+capture_timer!(tim4, TIM4, u16, apb1enr, apb1rstr, timclk1, tim4rst, tim4en, [TIM4_CH1: 1, TIM4_CH2: 2, TIM4_CH3: 3, TIM4_CH4: 4]);
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+pwm_input!(TIM4, TIM4_CH1, TIM4_CH2);
+
+#[cfg(feature = "stm32f373")]
+capture_timer!(tim5, TIM5, u32, apb1enr, apb1rstr, timclk1, tim5rst, tim5en, [TIM5_CH1: 1, TIM5_CH2: 2, TIM5_CH3: 3, TIM5_CH4: 4]);
+#[cfg(feature = "stm32f373")]
+pwm_input!(TIM5, TIM5_CH1, TIM5_CH2);
+
+#[cfg(any(feature = "stm32f303", feature = "stm32f358", feature = "stm32f398"))]
+capture_timer!(tim8, TIM8, u16, apb2enr, apb2rstr, timclk2, tim8rst, tim8en, [TIM8_CH1: 1, TIM8_CH2: 2, TIM8_CH3: 3, TIM8_CH4: 4]);
+#[cfg(any(feature = "stm32f303", feature = "stm32f358", feature = "stm32f398"))]
+pwm_input!(TIM8, TIM8_CH1, TIM8_CH2);
+
+#[cfg(feature = "stm32f373")]
+capture_timer!(tim12, TIM12, u16, apb1enr, apb1rstr, timclk1, tim12rst, tim12en, [TIM12_CH1: 1, TIM12_CH2: 2]);
+#[cfg(feature = "stm32f373")]
+pwm_input!(TIM12, TIM12_CH1, TIM12_CH2);
+
+#[cfg(feature = "stm32f373")]
+capture_timer!(tim13, TIM13, u16, apb1enr, apb1rstr, timclk1, tim13rst, tim13en, [TIM13_CH1: 1]);
+
+#[cfg(feature = "stm32f373")]
+capture_timer!(tim14, TIM14, u16, apb1enr, apb1rstr, timclk1, tim14rst, tim14en, [TIM14_CH1: 1]);
+
+capture_timer!(tim15, TIM15, u16, apb2enr, apb2rstr, timclk2, tim15rst, tim15en, [TIM15_CH1: 1, TIM15_CH2: 2]);
+pwm_input!(TIM15, TIM15_CH1, TIM15_CH2);
+
+capture_timer!(tim16, TIM16, u16, apb2enr, apb2rstr, timclk2, tim16rst, tim16en, [TIM16_CH1: 1]);
+
+capture_timer!(tim17, TIM17, u16, apb2enr, apb2rstr, timclk2, tim17rst, tim17en, [TIM17_CH1: 1]);
+
+#[cfg(feature = "stm32f373")]
+capture_timer!(tim19, TIM19, u16, apb2enr, apb2rstr, timclk2, tim19rst, tim19en, [TIM19_CH1: 1, TIM19_CH2: 2, TIM19_CH3: 3, TIM19_CH4: 4]);
+#[cfg(feature = "stm32f373")]
+pwm_input!(TIM19, TIM19_CH1, TIM19_CH2);
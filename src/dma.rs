@@ -319,6 +319,14 @@ pub trait Channel: private::Channel {
         self.ch().ndtr.write(|w| w.ndt().bits(len));
     }
 
+    /// Get the number of words, which are left to transfer.
+    ///
+    /// This is useful to find out, how many words have been received,
+    /// if a transfer is stopped early, e.g. on an idle receive line.
+    fn remaining_transfers(&self) -> u16 {
+        self.ch().ndtr.read().ndt().bits()
+    }
+
     /// Set the word size.
     ///
     /// # Panics
@@ -567,3 +575,18 @@ on_channel!(dma1,
     serial::Rx<pac::USART3> => C3,
     serial::Tx<pac::USART3> => C2,
 );
+
+#[cfg(any(
+    feature = "stm32f302xb",
+    feature = "stm32f302xc",
+    feature = "stm32f302xd",
+    feature = "stm32f302xe",
+    feature = "stm32f303xb",
+    feature = "stm32f303xc",
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+))]
+on_channel!(dma2,
+    serial::Rx<pac::UART4> => C3,
+    serial::Tx<pac::UART4> => C5,
+);
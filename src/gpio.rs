@@ -39,7 +39,11 @@ use crate::hal::digital::v2::InputPin;
 use crate::hal::digital::v2::OutputPin;
 #[cfg(feature = "unproven")]
 use crate::hal::digital::v2::StatefulOutputPin;
+use cortex_m::interrupt;
+
+use crate::pac::{self, EXTI};
 use crate::rcc::AHB;
+use crate::syscfg::SysCfg;
 
 /// Extension trait to split a GPIO peripheral in independent pins and registers
 pub trait GpioExt {
@@ -75,6 +79,30 @@ pub struct OpenDrain;
 /// Analog mode (type state)
 pub struct Analog;
 
+/// Dynamic mode (type state)
+///
+/// The mode of a pin in dynamic mode is not tracked by the type system,
+/// but can be switched between input and output at runtime,
+/// e.g. with `make_pull_up_input` or `make_push_pull_output`.
+/// This is useful for bit-banged protocols like 1-Wire, which use
+/// a single pin for both directions.
+///
+/// As the mode is only known at runtime, the [InputPin] and [OutputPin]
+/// implementations return [`PinModeError::IncorrectMode`],
+/// if the pin is not in a matching mode. The `make_...` methods return
+/// [`PinModeError::Locked`], if the configuration of the pin has been locked
+/// with the GPIOx_LCKR register and the mode can not be switched.
+pub struct Dynamic;
+
+/// Error returned by pins in [`Dynamic`] mode
+#[derive(Debug)]
+pub enum PinModeError {
+    /// The operation is not supported by the current mode of the pin
+    IncorrectMode,
+    /// The configuration of the pin is locked
+    Locked,
+}
+
 /// Alternate function 0 (type state)
 pub struct AF0;
 
@@ -123,11 +151,439 @@ pub struct AF14;
 /// Alternate function 15 (type state)
 pub struct AF15;
 
+/// Output speed of a GPIO pin
+///
+/// A higher speed setting increases the slew rate of the output driver,
+/// which is needed for fast signals, but also increases the power
+/// consumption and the electromagnetic noise.
+pub enum Speed {
+    /// Low speed (reset state)
+    Low = 0b00,
+    /// Medium speed
+    Medium = 0b01,
+    /// High speed
+    High = 0b11,
+}
+
+/// Signal edge on which an external interrupt is triggered
+pub enum Edge {
+    /// Trigger on the rising edge
+    Rising,
+    /// Trigger on the falling edge
+    Falling,
+    /// Trigger on both the rising and the falling edge
+    RisingFalling,
+}
+
+/// External interrupt (EXTI) configuration of an input pin
+///
+/// Every pin `Pxi` of a GPIO bank is routed to the EXTI line `i`.
+/// As only one GPIO bank can be connected to a line at a time,
+/// only one of `PA0`, `PB0`, ... can be an interrupt source for line 0.
+///
+/// ```
+/// let dp = pac::Peripherals::take().unwrap();
+/// let mut rcc = dp.RCC.constrain();
+/// let mut syscfg = dp.SYSCFG.constrain(&mut rcc.apb2);
+/// let mut exti = dp.EXTI;
+/// let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
+///
+/// let mut button = gpioa.pa0.into_pull_down_input(&mut gpioa.moder, &mut gpioa.pupdr);
+/// button.make_interrupt_source(&mut syscfg);
+/// button.trigger_on_edge(&mut exti, Edge::Rising);
+/// button.enable_interrupt(&mut exti);
+/// ```
+pub trait ExtiPin {
+    /// Routes the EXTI line of this pin to the GPIO bank of this pin
+    fn make_interrupt_source(&mut self, syscfg: &mut SysCfg);
+
+    /// Selects the signal edge(s) on which the EXTI line is triggered
+    fn trigger_on_edge(&mut self, exti: &mut EXTI, edge: Edge);
+
+    /// Unmasks the interrupt of the EXTI line
+    fn enable_interrupt(&mut self, exti: &mut EXTI);
+
+    /// Masks the interrupt of the EXTI line
+    fn disable_interrupt(&mut self, exti: &mut EXTI);
+
+    /// Clears the pending bit of the EXTI line
+    ///
+    /// This has to be done in the interrupt handler,
+    /// otherwise the interrupt is triggered again immediately.
+    fn clear_interrupt_pending_bit(&mut self);
+
+    /// Returns `true`, if an interrupt is pending on the EXTI line
+    fn check_interrupt(&self) -> bool;
+}
+
+// The EXTI registers of the first 32 lines are suffixed with `1`
+// on devices, which have more than 32 EXTI lines.
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        feature = "stm32f301",
+        feature = "stm32f318",
+        feature = "stm32f373",
+        feature = "stm32f378",
+        feature = "stm32f328",
+        feature = "stm32f358",
+        feature = "stm32f398",
+    ))] {
+        macro_rules! exti_reg {
+            ($exti:expr, $reg:ident) => {
+                $exti.$reg
+            };
+        }
+    } else {
+        macro_rules! exti_reg {
+            ($exti:expr, $reg:ident) => {
+                paste::paste! { $exti.[<$reg 1>] }
+            };
+        }
+    }
+}
+
+fn make_interrupt_source(syscfg: &mut SysCfg, port: u8, i: u8) {
+    let offset = 4 * (i % 4);
+    let port = u32::from(port);
+    // NOTE(unsafe) only the 4 bits of this EXTI line are touched
+    macro_rules! set_source {
+        ($exticr:ident) => {
+            syscfg.$exticr().modify(|r, w| unsafe {
+                w.bits((r.bits() & !(0b1111 << offset)) | (port << offset))
+            })
+        };
+    }
+    match i / 4 {
+        0 => set_source!(exticr1),
+        1 => set_source!(exticr2),
+        2 => set_source!(exticr3),
+        3 => set_source!(exticr4),
+        _ => crate::unreachable!(),
+    }
+}
+
+fn trigger_on_edge(exti: &mut EXTI, i: u8, edge: Edge) {
+    let (rising, falling) = match edge {
+        Edge::Rising => (true, false),
+        Edge::Falling => (false, true),
+        Edge::RisingFalling => (true, true),
+    };
+    // NOTE(unsafe) only the bit of this EXTI line is touched
+    exti_reg!(exti, rtsr).modify(|r, w| unsafe {
+        w.bits(if rising {
+            r.bits() | (1 << i)
+        } else {
+            r.bits() & !(1 << i)
+        })
+    });
+    exti_reg!(exti, ftsr).modify(|r, w| unsafe {
+        w.bits(if falling {
+            r.bits() | (1 << i)
+        } else {
+            r.bits() & !(1 << i)
+        })
+    });
+}
+
+fn enable_interrupt(exti: &mut EXTI, i: u8, enable: bool) {
+    // NOTE(unsafe) only the bit of this EXTI line is touched
+    exti_reg!(exti, imr).modify(|r, w| unsafe {
+        w.bits(if enable {
+            r.bits() | (1 << i)
+        } else {
+            r.bits() & !(1 << i)
+        })
+    });
+}
+
+fn clear_interrupt_pending_bit(i: u8) {
+    // NOTE(unsafe) atomic write to a write-1-to-clear register,
+    // which leaves all other lines untouched
+    unsafe { exti_reg!((*EXTI::ptr()), pr).write(|w| w.bits(1 << i)) };
+}
+
+fn check_interrupt(i: u8) -> bool {
+    // NOTE(unsafe) atomic read with no side effects
+    unsafe { exti_reg!((*EXTI::ptr()), pr).read().bits() & (1 << i) != 0 }
+}
+
+macro_rules! exti_pin {
+    ($self:ident => $port:expr, $i:expr) => {
+        fn make_interrupt_source(&mut $self, syscfg: &mut SysCfg) {
+            crate::gpio::make_interrupt_source(syscfg, $port, $i);
+        }
+
+        fn trigger_on_edge(&mut $self, exti: &mut EXTI, edge: Edge) {
+            crate::gpio::trigger_on_edge(exti, $i, edge);
+        }
+
+        fn enable_interrupt(&mut $self, exti: &mut EXTI) {
+            crate::gpio::enable_interrupt(exti, $i, true);
+        }
+
+        fn disable_interrupt(&mut $self, exti: &mut EXTI) {
+            crate::gpio::enable_interrupt(exti, $i, false);
+        }
+
+        fn clear_interrupt_pending_bit(&mut $self) {
+            crate::gpio::clear_interrupt_pending_bit($i);
+        }
+
+        fn check_interrupt(&$self) -> bool {
+            crate::gpio::check_interrupt($i)
+        }
+    };
+}
+
+/// Fully erased pin, which can be reconfigured at runtime
+///
+/// In contrast to [`PXx`], the GPIO bank and the pin number are stored
+/// together in a single byte, and the registers are accessed through the base
+/// address of the GPIO bank, so that no matching on the GPIO bank is needed.
+///
+/// The mode of the pin can be changed without the register proxies of the
+/// GPIO bank, as all register modifications are done inside of a critical
+/// section. Note, that the valid alternate functions of the pin are not
+/// checked, because the pin is not known at compile time.
+///
+/// The typed pins modify the same registers through the register proxies
+/// without a critical section. So an erased pin must not be reconfigured
+/// (e.g. in an interrupt handler), while a typed pin of the same GPIO bank
+/// is reconfigured, or the modification of the typed pin may be lost.
+///
+/// Every pin can be converted into a [`Pin`] with `erase()`.
+///
+/// ```
+/// let pins: [Pin<Input<Floating>>; 2] = [gpioa.pa0.erase(), gpiob.pb5.erase()];
+/// let [_, led] = pins;
+/// let led = led.into_push_pull_output();
+/// ```
+pub struct Pin<MODE> {
+    // GPIO bank index in the upper nibble, pin number in the lower nibble
+    index: u8,
+    _mode: PhantomData<MODE>,
+}
+
+// Sets `width` bits at `offset` of a register to `value`
+macro_rules! modify_bits {
+    ($reg:expr, $offset:expr, $width:expr, $value:expr) => {{
+        let offset = $offset;
+        let mask = (1 << $width) - 1;
+        $reg.modify(|r, w| unsafe { w.bits((r.bits() & !(mask << offset)) | ($value << offset)) });
+    }};
+}
+
+macro_rules! pin_into_af {
+    ($($AFi:ident: ($into_afi:ident, $af:expr),)+) => {
+        $(
+            paste::paste! {
+                #[doc = "Configures the pin to serve as alternate function: `" $AFi "`"]
+                ///
+                /// It is not checked, if the alternate function is available for this pin.
+                pub fn $into_afi(self) -> Pin<$AFi> {
+                    self.into_mode(|gpio, i| {
+                        if i < 8 {
+                            modify_bits!(gpio.afrl, 4 * i, 4, $af);
+                        } else {
+                            modify_bits!(gpio.afrh, 4 * (i - 8), 4, $af);
+                        }
+                        modify_bits!(gpio.moder, 2 * i, 2, 0b10);
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl<MODE> Pin<MODE> {
+    fn new(port: u8, i: u8) -> Self {
+        Pin {
+            index: (port << 4) | i,
+            _mode: PhantomData,
+        }
+    }
+
+    fn port(&self) -> u8 {
+        self.index >> 4
+    }
+
+    fn i(&self) -> u8 {
+        self.index & 0xf
+    }
+
+    fn gpio(&self) -> &'static pac::gpioa::RegisterBlock {
+        // NOTE(unsafe) The register blocks of all GPIO banks have the same layout
+        // and are placed 0x400 bytes apart, starting with GPIOA.
+        unsafe {
+            &*((pac::GPIOA::ptr() as usize + 0x400 * usize::from(self.port()))
+                as *const pac::gpioa::RegisterBlock)
+        }
+    }
+
+    fn into_mode<NEW>(self, f: impl FnOnce(&pac::gpioa::RegisterBlock, u32)) -> Pin<NEW> {
+        let gpio = self.gpio();
+        let i = u32::from(self.i());
+        interrupt::free(|_| f(gpio, i));
+        Pin {
+            index: self.index,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Configures the pin to operate as a floating input pin
+    pub fn into_floating_input(self) -> Pin<Input<Floating>> {
+        self.into_mode(|gpio, i| {
+            modify_bits!(gpio.moder, 2 * i, 2, 0b00);
+            modify_bits!(gpio.pupdr, 2 * i, 2, 0b00);
+        })
+    }
+
+    /// Configures the pin to operate as a pulled down input pin
+    pub fn into_pull_down_input(self) -> Pin<Input<PullDown>> {
+        self.into_mode(|gpio, i| {
+            modify_bits!(gpio.moder, 2 * i, 2, 0b00);
+            modify_bits!(gpio.pupdr, 2 * i, 2, 0b10);
+        })
+    }
+
+    /// Configures the pin to operate as a pulled up input pin
+    pub fn into_pull_up_input(self) -> Pin<Input<PullUp>> {
+        self.into_mode(|gpio, i| {
+            modify_bits!(gpio.moder, 2 * i, 2, 0b00);
+            modify_bits!(gpio.pupdr, 2 * i, 2, 0b01);
+        })
+    }
+
+    /// Configures the pin to operate as an open drain output pin
+    pub fn into_open_drain_output(self) -> Pin<Output<OpenDrain>> {
+        self.into_mode(|gpio, i| {
+            modify_bits!(gpio.moder, 2 * i, 2, 0b01);
+            modify_bits!(gpio.otyper, i, 1, 0b1);
+        })
+    }
+
+    /// Configures the pin to operate as an push pull output pin
+    pub fn into_push_pull_output(self) -> Pin<Output<PushPull>> {
+        self.into_mode(|gpio, i| {
+            modify_bits!(gpio.moder, 2 * i, 2, 0b01);
+            modify_bits!(gpio.otyper, i, 1, 0b0);
+        })
+    }
+
+    /// Configures the pin to operate as analog, with disabled schmitt trigger.
+    /// This mode is suitable when the pin is connected to the DAC or ADC.
+    pub fn into_analog(self) -> Pin<Analog> {
+        self.into_mode(|gpio, i| {
+            modify_bits!(gpio.moder, 2 * i, 2, 0b11);
+            modify_bits!(gpio.pupdr, 2 * i, 2, 0b00);
+        })
+    }
+
+    pin_into_af! {
+        AF0: (into_af0, 0),
+        AF1: (into_af1, 1),
+        AF2: (into_af2, 2),
+        AF3: (into_af3, 3),
+        AF4: (into_af4, 4),
+        AF5: (into_af5, 5),
+        AF6: (into_af6, 6),
+        AF7: (into_af7, 7),
+        AF8: (into_af8, 8),
+        AF9: (into_af9, 9),
+        AF10: (into_af10, 10),
+        AF11: (into_af11, 11),
+        AF12: (into_af12, 12),
+        AF13: (into_af13, 13),
+        AF14: (into_af14, 14),
+        AF15: (into_af15, 15),
+    }
+
+    /// Sets the output speed of the pin
+    pub fn set_speed(&mut self, speed: Speed) {
+        let gpio = self.gpio();
+        let i = u32::from(self.i());
+        interrupt::free(|_| modify_bits!(gpio.ospeedr, 2 * i, 2, speed as u32));
+    }
+}
+
+impl Pin<Output<OpenDrain>> {
+    /// Enables / disables the internal pull up
+    pub fn internal_pull_up(&mut self, on: bool) {
+        let gpio = self.gpio();
+        let i = u32::from(self.i());
+        interrupt::free(|_| modify_bits!(gpio.pupdr, 2 * i, 2, if on { 0b01 } else { 0b00 }));
+    }
+}
+
+impl<MODE> OutputPin for Pin<Output<MODE>> {
+    type Error = Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        // NOTE(unsafe, write) atomic write to a stateless register
+        unsafe { self.gpio().bsrr.write(|w| w.bits(1 << self.i())) };
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        // NOTE(unsafe, write) atomic write to a stateless register
+        unsafe { self.gpio().bsrr.write(|w| w.bits(1 << (16 + self.i()))) };
+        Ok(())
+    }
+}
+
+#[cfg(feature = "unproven")]
+impl<MODE> InputPin for Pin<Input<MODE>> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_low()?)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(self.gpio().idr.read().bits() & (1 << self.i()) == 0)
+    }
+}
+
+#[cfg(feature = "unproven")]
+impl InputPin for Pin<Output<OpenDrain>> {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(!self.is_low()?)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(self.gpio().idr.read().bits() & (1 << self.i()) == 0)
+    }
+}
+
+#[cfg(feature = "unproven")]
+impl<MODE> StatefulOutputPin for Pin<Output<MODE>> {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.is_set_low().map(|b| !b)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(self.gpio().odr.read().bits() & (1 << self.i()) == 0)
+    }
+}
+
+#[cfg(feature = "unproven")]
+impl<MODE> toggleable::Default for Pin<Output<MODE>> {}
+
+impl<MODE> ExtiPin for Pin<Input<MODE>> {
+    exti_pin!(self => self.port(), self.i());
+}
+
 macro_rules! gpio {
     ([
         $({
             GPIO: $GPIOX:ident,
             gpio: $gpiox:ident,
+            port_index: $port_index:expr,
             gpio_mapped: $gpioy:ident,
             gpio_mapped_ioenr: $iopxenr:ident,
             gpio_mapped_iorst: $iopxrst:ident,
@@ -136,6 +592,7 @@ macro_rules! gpio {
                 $($PXi:ident: (
                     $pxi:ident, $i:expr, $MODE:ty, $moderi:ident, $AFR:ident, $afri:ident,
                     $bsi:ident, $bri:ident, $odri:ident, $idri:ident, $pupdri:ident, $oti:ident,
+                    $ospeedri:ident,
                     { $( $AFi:ty: ($into_afi:ident, $afi:ident), )* },
                 ),)+
             ],
@@ -155,6 +612,16 @@ macro_rules! gpio {
             )+
         }
 
+        impl Gpio {
+            fn port_index(&self) -> u8 {
+                match self {
+                    $(
+                        Gpio::$GPIOX => $port_index,
+                    )+
+                }
+            }
+        }
+
         /// Fully erased pin
         ///
         /// This moves the pin type information to be known
@@ -170,6 +637,30 @@ macro_rules! gpio {
             _mode: PhantomData<MODE>,
         }
 
+        impl<MODE> PXx<MODE> {
+            /// Erases the GPIO bank and pin number into a single [`Pin`]
+            ///
+            /// This allows to change the mode of the pin at runtime.
+            pub fn erase(self) -> Pin<MODE> {
+                Pin::new(self.gpio.port_index(), self.i)
+            }
+
+            /// Sets the output speed of the pin
+            pub fn set_speed(&mut self, speed: Speed) {
+                let i = u32::from(self.i);
+                interrupt::free(|_| match &self.gpio {
+                    $(
+                        Gpio::$GPIOX => {
+                            // NOTE(unsafe) only the 2 bits of this pin are modified
+                            // inside of a critical section
+                            let gpio = unsafe { &*$GPIOX::ptr() };
+                            modify_bits!(gpio.ospeedr, 2 * i, 2, speed as u32);
+                        }
+                    )+
+                });
+            }
+        }
+
         impl<MODE> OutputPin for PXx<Output<MODE>> {
             type Error = Infallible;
 
@@ -259,6 +750,10 @@ macro_rules! gpio {
         #[cfg(feature = "unproven")]
         impl <MODE> toggleable::Default for PXx<Output<MODE>> {}
 
+        impl<MODE> ExtiPin for PXx<Input<MODE>> {
+            exti_pin!(self => self.gpio.port_index(), self.i);
+        }
+
         $(
             paste::paste!{
                 #[doc = "All Pins and associated functions for GPIO Bank: `" $GPIOX "`"]
@@ -273,15 +768,16 @@ macro_rules! gpio {
                     use crate::hal::digital::v2::StatefulOutputPin;
                     #[cfg(feature = "unproven")]
                     use crate::hal::digital::v2::toggleable;
-                    use crate::pac::{$gpioy, $GPIOX};
+                    use crate::pac::{$gpioy, $GPIOX, EXTI};
+                    use crate::syscfg::SysCfg;
 
                     use crate::rcc::AHB;
                     #[allow(unused_imports)]
                     use super::{AF0, AF1, AF2, AF3, AF4, AF5, AF6, AF7, AF8, AF9, AF10, AF11, AF12, AF13, AF14, AF15};
                     use super::{
-                        Floating, GpioExt, Input, OpenDrain, Output, Analog,
+                        Floating, GpioExt, Input, OpenDrain, Output, Analog, Dynamic, PinModeError,
                         PullDown, PullUp, PushPull,
-                        PXx, Gpio,
+                        PXx, Pin, Gpio, Edge, ExtiPin, Speed,
                     };
 
                     /// GPIO parts
@@ -292,6 +788,8 @@ macro_rules! gpio {
                         pub afrl: AFRL,
                         /// Opaque MODER register
                         pub moder: MODER,
+                        /// Opaque OSPEEDR register
+                        pub ospeedr: OSPEEDR,
                         /// Opaque OTYPER register
                         pub otyper: OTYPER,
                         /// Opaque PUPDR register
@@ -314,6 +812,7 @@ macro_rules! gpio {
                                 afrh: AFRH { _0: () },
                                 afrl: AFRL { _0: () },
                                 moder: MODER { _0: () },
+                                ospeedr: OSPEEDR { _0: () },
                                 otyper: OTYPER { _0: () },
                                 pupdr: PUPDR { _0: () },
                                 $(
@@ -363,6 +862,17 @@ macro_rules! gpio {
                         }
                     }
 
+                    /// Opaque OSPEEDR register
+                    pub struct OSPEEDR {
+                        _0: (),
+                    }
+
+                    impl OSPEEDR {
+                        pub(crate) fn ospeedr(&mut self) -> &$gpioy::OSPEEDR {
+                            unsafe { &(*$GPIOX::ptr()).ospeedr }
+                        }
+                    }
+
                     /// Opaque OTYPER register
                     pub struct OTYPER {
                         _0: (),
@@ -403,6 +913,22 @@ macro_rules! gpio {
                                 _mode: self._mode,
                             }
                         }
+
+                        /// Erases the GPIO bank and pin number into a single [`Pin`]
+                        ///
+                        /// This allows to change the mode of the pin at runtime.
+                        pub fn erase(self) -> Pin<MODE> {
+                            Pin::new($port_index, self.i)
+                        }
+
+                        /// Sets the output speed of the pin
+                        pub fn set_speed(&mut self, ospeedr: &mut OSPEEDR, speed: Speed) {
+                            let offset = 2 * self.i;
+                            // NOTE(unsafe) only the 2 bits of this pin are touched
+                            ospeedr.ospeedr().modify(|r, w| unsafe {
+                                w.bits((r.bits() & !(0b11 << offset)) | ((speed as u32) << offset))
+                            });
+                        }
                     }
 
                     impl<MODE> OutputPin for $PXx<Output<MODE>> {
@@ -464,6 +990,124 @@ macro_rules! gpio {
                     #[cfg(feature = "unproven")]
                     impl<MODE> toggleable::Default for $PXx<Output<MODE>> {}
 
+                    impl<MODE> ExtiPin for $PXx<Input<MODE>> {
+                        exti_pin!(self => $port_index, self.i);
+                    }
+
+                    /// Group of output pins of this GPIO bank, which are written at once
+                    ///
+                    /// All pins of the group are set and reset with a single write to the
+                    /// BSRR register, so that they change their state at the same time.
+                    ///
+                    /// ```
+                    /// use core::iter::once;
+                    ///
+                    /// // From Rust 1.53 on, an array of the pins can be passed instead
+                    /// let mut data = gpioa::OutputPort::new(
+                    ///     once(pa0.downgrade())
+                    ///         .chain(once(pa1.downgrade()))
+                    ///         .chain(once(pa2.downgrade())),
+                    /// );
+                    /// // Sets PA0 and PA2 and resets PA1
+                    /// data.write(0b101);
+                    /// ```
+                    pub struct OutputPort<MODE> {
+                        mask: u16,
+                        _mode: PhantomData<MODE>,
+                    }
+
+                    impl<MODE> OutputPort<MODE> {
+                        /// Groups the given output pins
+                        pub fn new<I>(pins: I) -> Self
+                        where
+                            I: IntoIterator<Item = $PXx<Output<MODE>>>,
+                        {
+                            OutputPort {
+                                mask: pins.into_iter().fold(0, |mask, pin| mask | (1 << pin.i)),
+                                _mode: PhantomData,
+                            }
+                        }
+
+                        /// Returns the mask of the pins in this group
+                        ///
+                        /// Bit `i` of the mask is set, if pin `i` of the GPIO bank
+                        /// is part of the group.
+                        pub fn mask(&self) -> u16 {
+                            self.mask
+                        }
+
+                        /// Writes the state of all pins of this group at once
+                        ///
+                        /// Bit `i` of `value` is the new state of pin `i` of the GPIO bank.
+                        /// Bits of pins, which are not part of this group, are ignored.
+                        pub fn write(&mut self, value: u16) {
+                            let set = u32::from(value & self.mask);
+                            let reset = u32::from(!value & self.mask);
+                            // NOTE(unsafe, write) atomic write to a stateless register
+                            unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.bits(set | (reset << 16))) }
+                        }
+
+                        /// Reads the output state of all pins of this group at once
+                        ///
+                        /// Bits of pins, which are not part of this group, are zero.
+                        pub fn read(&self) -> u16 {
+                            // NOTE(unsafe) atomic read with no side effects
+                            unsafe { (*$GPIOX::ptr()).odr.read().bits() as u16 & self.mask }
+                        }
+
+                        /// Releases the pins of this group
+                        pub fn release(self) -> impl Iterator<Item = $PXx<Output<MODE>>> {
+                            let mask = self.mask;
+                            (0..16)
+                                .filter(move |i| mask & (1 << i) != 0)
+                                .map(|i| $PXx { i, _mode: PhantomData })
+                        }
+                    }
+
+                    /// Group of input pins of this GPIO bank, which are read at once
+                    pub struct InputPort<MODE> {
+                        mask: u16,
+                        _mode: PhantomData<MODE>,
+                    }
+
+                    impl<MODE> InputPort<MODE> {
+                        /// Groups the given input pins
+                        pub fn new<I>(pins: I) -> Self
+                        where
+                            I: IntoIterator<Item = $PXx<Input<MODE>>>,
+                        {
+                            InputPort {
+                                mask: pins.into_iter().fold(0, |mask, pin| mask | (1 << pin.i)),
+                                _mode: PhantomData,
+                            }
+                        }
+
+                        /// Returns the mask of the pins in this group
+                        ///
+                        /// Bit `i` of the mask is set, if pin `i` of the GPIO bank
+                        /// is part of the group.
+                        pub fn mask(&self) -> u16 {
+                            self.mask
+                        }
+
+                        /// Reads the input state of all pins of this group at once
+                        ///
+                        /// Bit `i` of the returned value is the state of pin `i` of the
+                        /// GPIO bank. Bits of pins, which are not part of this group, are zero.
+                        pub fn read(&self) -> u16 {
+                            // NOTE(unsafe) atomic read with no side effects
+                            unsafe { (*$GPIOX::ptr()).idr.read().bits() as u16 & self.mask }
+                        }
+
+                        /// Releases the pins of this group
+                        pub fn release(self) -> impl Iterator<Item = $PXx<Input<MODE>>> {
+                            let mask = self.mask;
+                            (0..16)
+                                .filter(move |i| mask & (1 << i) != 0)
+                                .map(|i| $PXx { i, _mode: PhantomData })
+                        }
+                    }
+
                     $(
                         paste::paste! {
                             #[doc = "Pin `" $PXi "`"]
@@ -486,6 +1130,19 @@ macro_rules! gpio {
                                         afr.afr().modify(|_, w| w.$afri().$afi());
                                         $PXi { _mode: PhantomData }
                                     }
+
+                                    #[doc = "Configures `" $PXi "` to serve as alternate function: `" $AFi "` with an open drain output"]
+                                    ///
+                                    /// This is e.g. needed for the TX pin of a half-duplex serial interface.
+                                    pub fn [<$into_afi _open_drain>](
+                                        self,
+                                        moder: &mut MODER,
+                                        otyper: &mut OTYPER,
+                                        afr: &mut $AFR,
+                                    ) -> $PXi<$AFi> {
+                                        otyper.otyper().modify(|_, w| w.$oti().open_drain());
+                                        self.$into_afi(moder, afr)
+                                    }
                                 }
                             )*
 
@@ -555,6 +1212,162 @@ macro_rules! gpio {
                                 pupdr.pupdr().modify(|_,w| w.$pupdri().floating());
                                 $PXi { _mode: PhantomData }
                             }
+
+                            /// Erases the GPIO bank and pin number into a single [`Pin`]
+                            ///
+                            /// This allows to change the mode of the pin at runtime.
+                            pub fn erase(self) -> Pin<MODE> {
+                                Pin::new($port_index, $i)
+                            }
+
+                            /// Configures the pin to operate in dynamic mode
+                            ///
+                            /// The pin keeps its current configuration, until it is changed
+                            /// at runtime with one of the `make_...` methods.
+                            pub fn into_dynamic(self) -> $PXi<Dynamic> {
+                                $PXi { _mode: PhantomData }
+                            }
+
+                            /// Sets the output speed of the pin
+                            pub fn set_speed(&mut self, ospeedr: &mut OSPEEDR, speed: Speed) {
+                                ospeedr.ospeedr().modify(|_, w| w.$ospeedri().bits(speed as u8));
+                            }
+                        }
+
+                        impl $PXi<Dynamic> {
+                            /// Switches the pin to operate as a floating input pin
+                            pub fn make_floating_input(
+                                &mut self,
+                                moder: &mut MODER,
+                                pupdr: &mut PUPDR,
+                            ) -> Result<(), PinModeError> {
+                                if self.is_locked() {
+                                    return Err(PinModeError::Locked);
+                                }
+                                moder.moder().modify(|_, w| w.$moderi().input());
+                                pupdr.pupdr().modify(|_,w| w.$pupdri().floating());
+                                Ok(())
+                            }
+
+                            /// Switches the pin to operate as a pulled down input pin
+                            pub fn make_pull_down_input(
+                                &mut self,
+                                moder: &mut MODER,
+                                pupdr: &mut PUPDR,
+                            ) -> Result<(), PinModeError> {
+                                if self.is_locked() {
+                                    return Err(PinModeError::Locked);
+                                }
+                                moder.moder().modify(|_, w| w.$moderi().input());
+                                pupdr.pupdr().modify(|_,w| w.$pupdri().pull_down());
+                                Ok(())
+                            }
+
+                            /// Switches the pin to operate as a pulled up input pin
+                            pub fn make_pull_up_input(
+                                &mut self,
+                                moder: &mut MODER,
+                                pupdr: &mut PUPDR,
+                            ) -> Result<(), PinModeError> {
+                                if self.is_locked() {
+                                    return Err(PinModeError::Locked);
+                                }
+                                moder.moder().modify(|_, w| w.$moderi().input());
+                                pupdr.pupdr().modify(|_,w| w.$pupdri().pull_up());
+                                Ok(())
+                            }
+
+                            /// Switches the pin to operate as an open drain output pin
+                            pub fn make_open_drain_output(
+                                &mut self,
+                                moder: &mut MODER,
+                                otyper: &mut OTYPER,
+                            ) -> Result<(), PinModeError> {
+                                if self.is_locked() {
+                                    return Err(PinModeError::Locked);
+                                }
+                                otyper.otyper().modify(|_, w| w.$oti().open_drain());
+                                moder.moder().modify(|_, w| w.$moderi().output());
+                                Ok(())
+                            }
+
+                            /// Switches the pin to operate as a push pull output pin
+                            pub fn make_push_pull_output(
+                                &mut self,
+                                moder: &mut MODER,
+                                otyper: &mut OTYPER,
+                            ) -> Result<(), PinModeError> {
+                                if self.is_locked() {
+                                    return Err(PinModeError::Locked);
+                                }
+                                otyper.otyper().modify(|_, w| w.$oti().push_pull());
+                                moder.moder().modify(|_, w| w.$moderi().output());
+                                Ok(())
+                            }
+
+                            fn is_locked(&self) -> bool {
+                                // NOTE(unsafe) atomic read with no side effects. The register
+                                // blocks of all GPIO banks have the same layout, but the PAC
+                                // does not model the GPIOx_LCKR register for every bank.
+                                let gpio = $GPIOX::ptr() as *const crate::pac::gpioa::RegisterBlock;
+                                let lckr = unsafe { (*gpio).lckr.read().bits() };
+                                // The LCKK bit is set, while the configuration is locked
+                                lckr & (1 << 16) != 0 && lckr & (1 << $i) != 0
+                            }
+
+                            fn is_input(&self) -> bool {
+                                // NOTE(unsafe) atomic read with no side effects
+                                unsafe { (*$GPIOX::ptr()).moder.read().$moderi().is_input() }
+                            }
+
+                            fn is_output(&self) -> bool {
+                                // NOTE(unsafe) atomic read with no side effects
+                                unsafe { (*$GPIOX::ptr()).moder.read().$moderi().is_output() }
+                            }
+
+                            fn is_open_drain(&self) -> bool {
+                                // NOTE(unsafe) atomic read with no side effects
+                                unsafe { (*$GPIOX::ptr()).otyper.read().$oti().is_open_drain() }
+                            }
+                        }
+
+                        impl OutputPin for $PXi<Dynamic> {
+                            type Error = PinModeError;
+
+                            fn set_high(&mut self) -> Result<(), Self::Error> {
+                                if !self.is_output() {
+                                    return Err(PinModeError::IncorrectMode);
+                                }
+                                // NOTE(unsafe, write) atomic write to a stateless register
+                                unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.$bsi().set()) }
+                                Ok(())
+                            }
+
+                            fn set_low(&mut self) -> Result<(), Self::Error> {
+                                if !self.is_output() {
+                                    return Err(PinModeError::IncorrectMode);
+                                }
+                                // NOTE(unsafe, write) atomic write to a stateless register
+                                unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.$bri().reset()) }
+                                Ok(())
+                            }
+                        }
+
+                        #[cfg(feature = "unproven")]
+                        impl InputPin for $PXi<Dynamic> {
+                            type Error = PinModeError;
+
+                            fn is_high(&self) -> Result<bool, Self::Error> {
+                                Ok(!self.is_low()?)
+                            }
+
+                            fn is_low(&self) -> Result<bool, Self::Error> {
+                                if !(self.is_input() || (self.is_output() && self.is_open_drain())) {
+                                    return Err(PinModeError::IncorrectMode);
+                                }
+                                // NOTE(unsafe) atomic read with no side effects
+                                Ok(unsafe { (*$GPIOX::ptr()).idr.read().$idri().is_low()})
+                            }
                         }
 
                         impl $PXi<Output<OpenDrain>> {
@@ -652,6 +1465,10 @@ macro_rules! gpio {
 
                         #[cfg(feature = "unproven")]
                         impl<MODE> toggleable::Default for $PXi<Output<MODE>> {}
+
+                        impl<MODE> ExtiPin for $PXi<Input<MODE>> {
+                            exti_pin!(self => $port_index, $i);
+                        }
                     )+
                 }
             }
@@ -675,6 +1492,7 @@ macro_rules! gpio {
                 $({
                     GPIO: [<GPIO $X>],
                     gpio: [<gpio $x>],
+                    port_index: port_index!($X),
                     gpio_mapped: $gpioy,
                     gpio_mapped_ioenr: [<iop $x en>],
                     gpio_mapped_iorst: [<iop $x rst>],
@@ -683,6 +1501,7 @@ macro_rules! gpio {
                         $([<P $X $i>]: (
                             [<p $x $i>], $i, $mode, [<moder $i>], [<AFR $LH>], [<afr $lh $i>],
                             [<bs $i>], [<br $i>], [<odr $i>], [<idr $i>], [<pupdr $i>], [<ot $i>],
+                            [<ospeedr $i>],
                             { $( [<AF $af>]: ([<into_af $af>], [<af $af>]), )* },
                         ),)+
                     ],
@@ -691,6 +1510,35 @@ macro_rules! gpio {
         }
     };
 }
+
+// Index of a GPIO bank as used by the SYSCFG_EXTICRx registers
+macro_rules! port_index {
+    (A) => {
+        0
+    };
+    (B) => {
+        1
+    };
+    (C) => {
+        2
+    };
+    (D) => {
+        3
+    };
+    (E) => {
+        4
+    };
+    (F) => {
+        5
+    };
+    (G) => {
+        6
+    };
+    (H) => {
+        7
+    };
+}
+
 // auto-generated using codegen
 // STM32CubeMX DB release: DB.6.0.0
 
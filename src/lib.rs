@@ -177,16 +177,21 @@ pub use crate::pac::interrupt;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "device-selected")] {
+        pub mod capture;
         pub mod delay;
         pub mod flash;
         pub mod gpio;
         pub mod i2c;
+        pub mod monotonic;
         pub mod prelude;
         pub mod pwm;
+        pub mod pwr;
+        pub mod qei;
         pub mod rcc;
         pub mod rtc;
         pub mod serial;
         pub mod spi;
+        pub mod syscfg;
         pub mod time;
         pub mod timer;
     }
@@ -0,0 +1,240 @@
+//! Monotonic timer on a 32-bit timer
+//!
+//! In contrast to [`time::MonoTimer`](crate::time::MonoTimer), which counts
+//! the core clock cycles with the DWT, this monotonic timer uses a 32-bit
+//! general purpose timer (TIM2 or TIM5) with a configurable frequency, and
+//! can raise an interrupt at a given instant via the compare match of
+//! channel 1. This is what schedulers like RTIC need from a monotonic
+//! timer, so they do not have to occupy the system timer (SysTick).
+//!
+//! The counter wraps around after `2^32` ticks. [`Instant`]s are compared
+//! with wrapping arithmetic, so an instant is earlier than another one, if it
+//! is less than `2^31` ticks before it.
+//!
+//! With the `rtic-monotonic` feature, the timer implements the
+//! [`rtic_monotonic::Monotonic`] trait and can be used as monotonic of RTIC.
+//!
+//! ```
+//! let mut mono = Monotonic::tim2(dp.TIM2, 1.mhz(), &clocks, &mut rcc.apb1);
+//!
+//! // Raise the TIM2 interrupt in 10 ms
+//! let now = mono.now();
+//! mono.set_compare(now + Duration(10_000));
+//! mono.enable_compare_interrupt();
+//! ```
+
+use core::cmp::Ordering;
+use core::ops::{Add, Sub};
+
+use crate::pac::TIM2;
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+use crate::pac::TIM5;
+use crate::rcc::{Clocks, APB1};
+use crate::time::Hertz;
+use crate::timer::{prescaler, Timeout};
+
+/// Instant of a [`Monotonic`] timer in ticks of the timer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instant(pub u32);
+
+/// Duration in ticks of a [`Monotonic`] timer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration(pub u32);
+
+impl Ord for Instant {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Wrapping comparison, which is correct for instants
+        // less than `2^31` ticks apart
+        (self.0.wrapping_sub(other.0) as i32).cmp(&0)
+    }
+}
+
+impl PartialOrd for Instant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Instant {
+        Instant(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Instant {
+        Instant(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Duration {
+        Duration(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+/// Monotonic timer
+pub struct Monotonic<TIM> {
+    tim: TIM,
+    frequency: Hertz,
+}
+
+macro_rules! monotonic {
+    ($({
+        $TIMX:ident: ($tim:ident, $timXen:ident, $timXrst:ident),
+    },)+) => {
+        $(
+            impl Monotonic<$TIMX> {
+                /// Configures a TIM peripheral as a free running monotonic timer,
+                /// which counts with the given frequency
+                ///
+                /// The timer clock is divided by the prescaler of the timer,
+                /// so the frequency is rounded to the nearest reachable frequency.
+                /// Use [`frequency`](Self::frequency) to get the actual frequency.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the frequency can not be reached with the timer clock.
+                pub fn $tim<T>(tim: $TIMX, frequency: T, clocks: &Clocks, apb1: &mut APB1) -> Self
+                where
+                    T: Into<Hertz>,
+                {
+                    // enable and reset peripheral to a clean slate state
+                    apb1.enr().modify(|_, w| w.$timXen().enabled());
+                    apb1.rstr().modify(|_, w| w.$timXrst().reset());
+                    apb1.rstr().modify(|_, w| w.$timXrst().clear_bit());
+
+                    let timer_clock = clocks.timclk1();
+                    let ticks = Timeout::Frequency(frequency.into()).ticks(timer_clock);
+                    let psc = crate::expect!(prescaler(ticks, 1).ok(), "Unreachable frequency");
+                    // NOTE(write): uses all bits in this register.
+                    tim.psc.write(|w| w.psc().bits(psc));
+
+                    // Count through the whole 32-bit range
+                    // NOTE(write): uses all bits in this register.
+                    tim.arr.write(|w| unsafe { w.bits(u32::MAX) });
+
+                    // Load the prescaler value
+                    // NOTE(write): uses all bits in this register.
+                    tim.egr.write(|w| w.ug().update());
+                    tim.sr.modify(|_, w| w.uif().clear());
+
+                    // start counter
+                    tim.cr1.modify(|_, w| w.cen().enabled());
+
+                    Monotonic {
+                        tim,
+                        frequency: Hertz(timer_clock.0 / (u32::from(psc) + 1)),
+                    }
+                }
+
+                /// Returns the frequency, with which the timer counts
+                pub fn frequency(&self) -> Hertz {
+                    self.frequency
+                }
+
+                /// Returns the current instant
+                pub fn now(&self) -> Instant {
+                    Instant(self.tim.cnt.read().bits())
+                }
+
+                /// Sets the instant, at which the compare match
+                /// (and the interrupt, if enabled) happens
+                ///
+                /// This also clears a pending compare match. If the instant
+                /// is not in the future, the compare match is raised at once
+                /// instead of after the wrap-around of the counter.
+                pub fn set_compare(&mut self, instant: Instant) {
+                    // NOTE(write): uses all bits in this register.
+                    self.tim.ccr1.write(|w| unsafe { w.bits(instant.0) });
+                    self.clear_compare_flag();
+
+                    // Checked after the flag is cleared, so a match in between
+                    // is raised again instead of being lost
+                    if instant <= self.now() {
+                        // NOTE(write): only generates the compare event of channel 1.
+                        self.tim.egr.write(|w| w.cc1g().set_bit());
+                    }
+                }
+
+                /// Returns `true`, if the compare match happened
+                pub fn is_compare_match(&self) -> bool {
+                    self.tim.sr.read().cc1if().bit_is_set()
+                }
+
+                /// Clears the compare match flag
+                pub fn clear_compare_flag(&mut self) {
+                    self.tim.sr.modify(|_, w| w.cc1if().clear_bit());
+                }
+
+                /// Enables the interrupt on the compare match
+                pub fn enable_compare_interrupt(&mut self) {
+                    self.tim.dier.modify(|_, w| w.cc1ie().set_bit());
+                }
+
+                /// Disables the interrupt on the compare match
+                pub fn disable_compare_interrupt(&mut self) {
+                    self.tim.dier.modify(|_, w| w.cc1ie().clear_bit());
+                }
+
+                /// Resets the counter to zero
+                pub fn reset(&mut self) {
+                    // NOTE(write): uses all bits in this register.
+                    self.tim.cnt.write(|w| unsafe { w.bits(0) });
+                }
+
+                /// Stops the timer and releases the TIM peripheral
+                pub fn release(self) -> $TIMX {
+                    self.tim.cr1.modify(|_, w| w.cen().disabled());
+                    self.tim
+                }
+            }
+
+            #[cfg(feature = "rtic-monotonic")]
+            impl rtic_monotonic::Monotonic for Monotonic<$TIMX> {
+                type Instant = Instant;
+                type Duration = Duration;
+
+                fn now(&mut self) -> Instant {
+                    Monotonic::<$TIMX>::now(self)
+                }
+
+                fn set_compare(&mut self, instant: Instant) {
+                    Monotonic::<$TIMX>::set_compare(self, instant);
+                }
+
+                fn clear_compare_flag(&mut self) {
+                    Monotonic::<$TIMX>::clear_compare_flag(self);
+                }
+
+                fn zero() -> Instant {
+                    Instant(0)
+                }
+
+                unsafe fn reset(&mut self) {
+                    Monotonic::<$TIMX>::reset(self);
+                    self.enable_compare_interrupt();
+                }
+            }
+        )+
+    };
+}
+
+monotonic! {
+    {
+        TIM2: (tim2, tim2en, tim2rst),
+    },
+}
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+monotonic! {
+    {
+        TIM5: (tim5, tim5en, tim5rst),
+    },
+}
@@ -3,9 +3,11 @@
 #[cfg(any(feature = "stm32f302", feature = "stm32f303"))]
 pub use crate::dma::DmaExt as _stm32f3xx_hal_dma_DmaExt;
 pub use crate::flash::FlashExt as _stm32f3xx_hal_flash_FlashExt;
+pub use crate::gpio::ExtiPin as _stm32f3xx_hal_gpio_ExtiPin;
 pub use crate::gpio::GpioExt as _stm32f3xx_hal_gpio_GpioExt;
 pub use crate::hal::prelude::*;
 pub use crate::rcc::RccExt as _stm32f3xx_hal_rcc_RccExt;
+pub use crate::syscfg::SysCfgExt as _stm32f3xx_hal_syscfg_SysCfgExt;
 pub use crate::time::U32Ext as _stm32f3xx_hal_time_U32Ext;
 #[cfg(feature = "unproven")]
 pub use crate::{
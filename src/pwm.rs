@@ -150,6 +150,58 @@
   allowed to use other complementary pins.  PA8 is a valid choice if
   we have no pins in use, but it cannot be used once we've used PA7.
 
+  ## Dead time and break inputs
+
+  The timers with complementary outputs (TIM1, TIM8, TIM15, TIM16,
+  TIM17 and TIM20) also have a `_with_break` constructor, which
+  additionally returns the [`Break`] control of the timer.  It is used
+  to insert a dead time between a channel and its complementary output
+  and to connect the break input pins, which disable all outputs of the
+  timer, e.g. on an overcurrent condition.
+
+  ```
+    let ((c1_no_pins, _, _, _), mut brk) =
+        tim1_with_break(dp.TIM1, 9000, 20.khz(), &clocks, Config::default());
+
+    brk.set_dead_time(500.ns()).unwrap();
+    brk.enable_break_input(pa6, BreakPolarity::ActiveLow, 0);
+
+    if brk.is_break_triggered() {
+        // Fix the cause of the break first
+        brk.rearm();
+    }
+  ```
+
+  ## Alignment, PWM mode and one-pulse mode
+
+  By default, the timers count up and the channels are active (high)
+  as long as the counter is below the duty cycle.  The constructors with
+  the `_with_config` suffix accept a [`Config`] to select the
+  center-aligned modes, PWM mode 2, active low outputs and the
+  one-pulse mode.
+
+  ```
+    let config = Config {
+        alignment: Alignment::Center1,
+        ..Config::default()
+    };
+    let (c1_no_pins, _, _, _) = tim3_with_config(dp.TIM3, 9000, 20.khz(), &clocks, config);
+  ```
+
+  ## Synchronizing timers
+
+  The PWM timers can be chained with other timers via their trigger
+  output and the internal trigger inputs, e.g. to start TIM8
+  together with TIM1:
+
+  ```
+    tim1_ch1.set_master_mode(MasterMode::Enable);
+    tim8_ch1.set_slave_mode::<TIM1>(SlaveMode::Trigger);
+  ```
+
+  The channels of TIM20 only support the master mode, as no other
+  timer is connected to its internal trigger inputs.
+
   A usage example can be found at [examples/pwm.rs]
 
   [examples/pwm.rs]: https://github.com/stm32-rs/stm32f3xx-hal/blob/v0.6.0/examples/pwm.rs
@@ -160,10 +212,14 @@ use crate::{
     hal::PwmPin,
     pac::{RCC, TIM15, TIM16, TIM17, TIM2},
     rcc::Clocks,
-    time::Hertz,
+    time::{Hertz, NanoSeconds},
+    timer::{prescaler, MasterMode, Timeout},
 };
+use core::convert::TryInto;
 use core::marker::PhantomData;
 
+use crate::timer::{InternalTrigger, SlaveMode};
+
 #[cfg(any(
     feature = "stm32f302xb",
     feature = "stm32f302xc",
@@ -193,11 +249,19 @@ use crate::gpio::gpiod;
 ))]
 use crate::gpio::gpioe;
 #[cfg(any(
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+    feature = "stm32f398"
+))]
+use crate::gpio::gpiog;
+#[cfg(any(
+    feature = "stm32f301",
     feature = "stm32f318",
     feature = "stm32f302",
     feature = "stm32f303",
     feature = "stm32f373",
     feature = "stm32f378",
+    feature = "stm32f328",
     feature = "stm32f334",
     feature = "stm32f358",
     feature = "stm32f398",
@@ -231,6 +295,61 @@ pub struct WithPins {}
 /// Type state used to represent a channel is using (only) complementary pins
 pub struct WithNPins {}
 
+/// Pin, which is connected to the (non-complementary) timer channel `TIMx_CHy`
+///
+/// This is implemented for every pin, which can be used with a
+/// `output_to_...` method of a [`PwmChannel`], which is not a
+/// complementary output. These pins can also serve as inputs,
+/// e.g. for [input capture](crate::capture).
+///
+/// # Safety
+///
+/// The pin has to be connected to the channel in its current alternate
+/// function mode, as the timer is configured without checking the pin.
+pub unsafe trait ChannelPin<TIMx_CHy> {}
+
+/// Pin, which can be used as break input (BKIN) of the timer `TIM`
+///
+/// # Safety
+///
+/// The pin has to be connected to the break input in its current alternate
+/// function mode, as the break input is enabled without checking the pin.
+pub unsafe trait BreakPin<TIM> {}
+
+/// Pin, which can be used as second break input (BKIN2) of the timer `TIM`
+///
+/// # Safety
+///
+/// The pin has to be connected to the second break input in its current
+/// alternate function mode, as the break input is enabled without checking the pin.
+pub unsafe trait Break2Pin<TIM> {}
+
+/// Active level of a break input
+pub enum BreakPolarity {
+    /// The break input is active, when the pin is low
+    ActiveLow,
+    /// The break input is active, when the pin is high
+    ActiveHigh,
+}
+
+/// Dead time error
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeadTimeError {
+    /// The dead time is longer than the dead-time generator can insert
+    /// with the current timer clock
+    TooLong,
+}
+
+/// Break and dead-time control of a timer with complementary outputs
+///
+/// An instance is returned by the `_with_break` constructors, e.g.
+/// [`tim15_with_break`].
+pub struct Break<TIM> {
+    clock: Hertz,
+    tim: PhantomData<TIM>,
+}
+
 /// Representation of a Channel for an abritary timer channel,
 /// that also holds a type state for whether or not this channel
 /// is using any pins yet.
@@ -239,10 +358,73 @@ pub struct WithNPins {}
 pub struct PwmChannel<X, T> {
     timx_chy: PhantomData<X>,
     pin_status: PhantomData<T>,
+    mode: PwmMode,
+    polarity: Polarity,
+}
+
+/// Alignment of the PWM signals, selected by the counting direction of the timer
+#[derive(Clone, Copy, PartialEq)]
+pub enum Alignment {
+    /// The counter counts up (edge-aligned mode)
+    Edge,
+    /// The counter counts up and down, compare interrupts
+    /// are set only while counting down (center-aligned mode 1)
+    Center1,
+    /// The counter counts up and down, compare interrupts
+    /// are set only while counting up (center-aligned mode 2)
+    Center2,
+    /// The counter counts up and down, compare interrupts
+    /// are set while counting up and down (center-aligned mode 3)
+    Center3,
+}
+
+/// PWM mode of the channels
+#[derive(Clone, Copy, PartialEq)]
+pub enum PwmMode {
+    /// The channel is active, as long as the counter is lower than the duty cycle
+    Mode1,
+    /// The channel is inactive, as long as the counter is lower than the duty cycle
+    Mode2,
+}
+
+/// Polarity of the output pins of the channels
+#[derive(Clone, Copy, PartialEq)]
+pub enum Polarity {
+    /// The output pin is high, when the channel is active
+    ActiveHigh,
+    /// The output pin is low, when the channel is active
+    ActiveLow,
+}
+
+/// Configuration of the PWM timer and its channels
+///
+/// The default configuration is edge-aligned PWM mode 1
+/// with active high outputs.
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Alignment of the PWM signals
+    pub alignment: Alignment,
+    /// PWM mode of all channels
+    pub mode: PwmMode,
+    /// Polarity of all output pins
+    pub polarity: Polarity,
+    /// Whether the counter stops after a single period (one-pulse mode)
+    pub one_pulse: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            alignment: Alignment::Edge,
+            mode: PwmMode::Mode1,
+            polarity: Polarity::ActiveHigh,
+            one_pulse: false,
+        }
+    }
 }
 
 macro_rules! pwm_timer_private {
-    ($timx:ident, $TIMx:ty, $res:ty, $apbxenr:ident, $apbxrstr:ident, $pclkz:ident, $timxrst:ident, $timxen:ident, $enable_break_timer:expr, [$($TIMx_CHy:ident),+], [$($x:ident),+]) => {
+    ($timx:ident, $TIMx:ident, $res:ty, $apbxenr:ident, $apbxrstr:ident, $timclkz:ident, $timxrst:ident, $timxen:ident, $enable_break_timer:expr, [$($TIMx_CHy:ident),+], [$($x:ident),+]) => {
         /// Create one or more output channels from a TIM Peripheral
         /// This function requires the maximum resolution of the duty cycle,
         /// the period of the PWM signal and the frozen clock configuration.
@@ -253,66 +435,155 @@ macro_rules! pwm_timer_private {
         /// a resolution of 9000.  This allows the servo to be set in increments
         /// of exactly one degree.
         #[allow(unused_parens)]
-        pub fn $timx(tim: $TIMx, res: $res, freq: Hertz, clocks: &Clocks) -> ($(PwmChannel<$TIMx_CHy, NoPins>),+) {
-            // Power the timer and reset it to ensure a clean state
-            // We use unsafe here to abstract away this implementation detail
-            // Justification: It is safe because only scopes with mutable references
-            // to TIMx should ever modify this bit.
-            unsafe {
-                (*RCC::ptr()).$apbxenr.modify(|_, w| w.$timxen().set_bit());
-                (*RCC::ptr()).$apbxrstr.modify(|_, w| w.$timxrst().set_bit());
-                (*RCC::ptr()).$apbxrstr.modify(|_, w| w.$timxrst().clear_bit());
-            }
+        pub fn $timx<F: Into<Hertz>>(tim: $TIMx, res: $res, freq: F, clocks: &Clocks) -> ($(PwmChannel<$TIMx_CHy, NoPins>),+) {
+            paste::paste! { [<$timx _with_config>](tim, res, freq, clocks, Config::default()) }
+        }
+
+        paste::paste! {
+            /// Create one or more output channels from a TIM Peripheral,
+            /// like the constructor without the `_with_config` suffix does,
+            /// but with the given alignment, PWM mode, polarity and
+            /// one-pulse mode.
+            ///
+            /// In center-aligned mode, the counter counts up and down,
+            /// so the prescaler is halved to keep the frequency of the PWM signal.
+            ///
+            /// In one-pulse mode, the counter is not started. Use
+            /// [`start`](PwmChannel::start) of one of the channels to output a pulse.
+            ///
+            /// # Panics
+            ///
+            /// Panics, if the timer does not support the center-aligned mode
+            /// (TIM12 to TIM17) or the one-pulse mode (TIM13, TIM14).
+            #[allow(unused_parens)]
+            pub fn [<$timx _with_config>]<F: Into<Hertz>>(tim: $TIMx, res: $res, freq: F, clocks: &Clocks, config: Config) -> ($(PwmChannel<$TIMx_CHy, NoPins>),+) {
+                let (center_aligned_support, one_pulse_support) = pwm_timer_features!($TIMx);
+                crate::assert!(center_aligned_support || config.alignment == Alignment::Edge);
+                crate::assert!(one_pulse_support || !config.one_pulse);
+
+                // Power the timer and reset it to ensure a clean state
+                // We use unsafe here to abstract away this implementation detail
+                // Justification: It is safe because only scopes with mutable references
+                // to TIMx should ever modify this bit.
+                unsafe {
+                    (*RCC::ptr()).$apbxenr.modify(|_, w| w.$timxen().set_bit());
+                    (*RCC::ptr()).$apbxrstr.modify(|_, w| w.$timxrst().set_bit());
+                    (*RCC::ptr()).$apbxrstr.modify(|_, w| w.$timxrst().clear_bit());
+                }
+
+                // enable auto reload preloader
+                tim.cr1.modify(|_, w| w.arpe().set_bit());
+
+                // Select the alignment (CMS bits) and the one-pulse mode (OPM bit)
+                //
+                // NOTE(unsafe): not all timers have these bits documented in stm32f3,
+                // the configuration is checked against the timer above.
+                let cms = match config.alignment {
+                    Alignment::Edge => 0b00,
+                    Alignment::Center1 => 0b01,
+                    Alignment::Center2 => 0b10,
+                    Alignment::Center3 => 0b11,
+                };
+                let opm = u32::from(config.one_pulse);
+                #[allow(unused_unsafe)]
+                tim.cr1.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0b11 << 5 | 1 << 3)) | cms << 5 | opm << 3)
+                });
+
+                // Set the "resolution" of the duty cycle (ticks before restarting at 0)
+                // Oddly this is unsafe for some timers and not others
+                //
+                // NOTE(write): not all timers are documented in stm32f3, thus marked unsafe.
+                // This write uses all bits of this register so there are no unknown side effects.
+                #[allow(unused_unsafe)]
+                tim.arr.write(|w| unsafe {
+                    w.arr().bits(res)
+                });
+
+                // Set the pre-scaler
+                let ticks = Timeout::Frequency(freq.into()).ticks(clocks.$timclkz());
+                // In center-aligned mode the counter counts up and down in a period
+                let counts = if config.alignment == Alignment::Edge { 1 } else { 2 } * u64::from(res);
+                let psc = crate::expect!(prescaler(ticks, counts).ok(), "Unreachable PWM frequency");
+                // NOTE(write): uses all bits of this register.
+                tim.psc.write(|w| w.psc().bits(psc));
+
+                // Make the settings reload immediately
+                // NOTE(write): write to a state-less register.
+                tim.egr.write(|w| w.ug().set_bit());
+
+                // Enable outputs (STM32 Break Timer Specific)
+                $enable_break_timer(&tim);
+
+                // Enable the Timer
+                // In one-pulse mode, the counter would stop after the first pulse
+                if !config.one_pulse {
+                    tim.cr1.modify(|_, w| w.cen().set_bit());
+                }
 
-            // enable auto reload preloader
-            tim.cr1.modify(|_, w| w.arpe().set_bit());
-
-            // Set the "resolution" of the duty cycle (ticks before restarting at 0)
-            // Oddly this is unsafe for some timers and not others
-            //
-            // NOTE(write): not all timers are documented in stm32f3, thus marked unsafe.
-            // This write uses all bits of this register so there are no unknown side effects.
-            #[allow(unused_unsafe)]
-            tim.arr.write(|w| unsafe {
-                w.arr().bits(res)
-            });
-
-            // Set the pre-scaler
-            // TODO: This is repeated in the timer/pwm module.
-            // It might make sense to move into the clocks as a crate-only property.
-            // TODO: ppre1 is used in timer.rs (never ppre2), should this be dynamic?
-            let clock_freq = clocks.$pclkz().0 * if clocks.ppre1() == 1 { 1 } else { 2 };
-            let prescale_factor = clock_freq / res as u32 / freq.0;
-            // NOTE(write): uses all bits of this register.
-            tim.psc.write(|w| w.psc().bits(prescale_factor as u16 - 1));
-
-            // Make the settings reload immediately
-            // NOTE(write): write to a state-less register.
-            tim.egr.write(|w| w.ug().set_bit());
-
-            // Enable outputs (STM32 Break Timer Specific)
-            $enable_break_timer(&tim);
-
-            // Enable the Timer
-            tim.cr1.modify(|_, w| w.cen().set_bit());
-
-            // TODO: Passing in the constructor is a bit silly,
-            // is there an alternative approach to get this to repeat,
-            // even though its not dynamic?
-            ($($x { timx_chy: PhantomData, pin_status: PhantomData }),+)
+                // TODO: Passing in the constructor is a bit silly,
+                // is there an alternative approach to get this to repeat,
+                // even though its not dynamic?
+                ($($x {
+                    timx_chy: PhantomData,
+                    pin_status: PhantomData,
+                    mode: config.mode,
+                    polarity: config.polarity,
+                }),+)
+            }
         }
+
+        $(
+            impl<T> PwmChannel<$TIMx_CHy, T> {
+                /// Starts the counter of the timer, which is shared by all
+                /// channels of this timer.
+                ///
+                /// In one-pulse mode, this outputs a single pulse.
+                pub fn start(&mut self) {
+                    // NOTE(unsafe): Only the CEN bit is set.
+                    unsafe {
+                        (*$TIMx::ptr()).cr1.modify(|_, w| w.cen().set_bit());
+                    }
+                }
+            }
+        )+
     }
 }
 
+// Support of the center-aligned mode and the one-pulse mode of the timers
+macro_rules! pwm_timer_features {
+    (TIM12) => {
+        (false, true)
+    };
+    (TIM13) => {
+        (false, false)
+    };
+    (TIM14) => {
+        (false, false)
+    };
+    (TIM15) => {
+        (false, true)
+    };
+    (TIM16) => {
+        (false, true)
+    };
+    (TIM17) => {
+        (false, true)
+    };
+    ($TIMx:ident) => {
+        (true, true)
+    };
+}
+
 macro_rules! pwm_timer_basic {
-    ($timx:ident, $TIMx:ty, $res:ty, $apbxenr:ident, $apb1rstr:ident, $pclkz:ident, $timxrst:ident, $timxen:ident, [$($TIMx_CHy:ident),+], [$($x:ident),+]) => {
+    ($timx:ident, $TIMx:ident, $res:ty, $apbxenr:ident, $apb1rstr:ident, $timclkz:ident, $timxrst:ident, $timxen:ident, [$($TIMx_CHy:ident),+], [$($x:ident),+]) => {
         pwm_timer_private!(
             $timx,
             $TIMx,
             $res,
             $apbxenr,
             $apb1rstr,
-            $pclkz,
+            $timclkz,
             $timxrst,
             $timxen,
             |_| (),
@@ -323,25 +594,288 @@ macro_rules! pwm_timer_basic {
 }
 
 macro_rules! pwm_timer_with_break {
-    ($timx:ident, $TIMx:ty, $res:ty, $apbxenr:ident, $apbxrstr:ident, $pclkz:ident, $timxrst:ident, $timxen:ident, [$($TIMx_CHy:ident),+], [$($x:ident),+]) => {
+    ($timx:ident, $TIMx:ident, $res:ty, $apbxenr:ident, $apbxrstr:ident, $timclkz:ident, $timxrst:ident, $timxen:ident, [$($TIMx_CHy:ident),+], [$($x:ident),+]) => {
         pwm_timer_private!(
             $timx,
             $TIMx,
             $res,
             $apbxenr,
             $apbxrstr,
-            $pclkz,
+            $timclkz,
             $timxrst,
             $timxen,
             |tim: &$TIMx| tim.bdtr.modify(|_, w| w.moe().set_bit()),
             [$($TIMx_CHy),+],
             [$($x),+]
         );
+
+        paste::paste! {
+            /// Create one or more output channels from a TIM Peripheral,
+            /// like the constructor with the `_with_config` suffix does,
+            /// and additionally return the [`Break`] control of this timer.
+            ///
+            /// The [`Break`] control is used to configure the dead time
+            /// of the complementary outputs and the break input(s).
+            #[allow(unused_parens)]
+            pub fn [<$timx _with_break>]<F: Into<Hertz>>(tim: $TIMx, res: $res, freq: F, clocks: &Clocks, config: Config) -> (($(PwmChannel<$TIMx_CHy, NoPins>),+), Break<$TIMx>) {
+                let channels = [<$timx _with_config>](tim, res, freq, clocks, config);
+                let clock = clocks.$timclkz();
+                (channels, Break { clock, tim: PhantomData })
+            }
+        }
+
+        impl Break<$TIMx> {
+            /// Sets the dead time, which is inserted between the switching
+            /// of a channel and its complementary output.
+            ///
+            /// The dead time is rounded down to the next possible value.
+            /// Returns [`DeadTimeError::TooLong`], if the dead time
+            /// can not be reached with the current timer clock or does not
+            /// fit into [`NanoSeconds`].
+            ///
+            /// The dead time is counted in periods of the timer clock, as the
+            /// clock division (CKD bits of the TIMx_CR1 register) is kept at 1.
+            pub fn set_dead_time<T: TryInto<NanoSeconds>>(&mut self, dead_time: T) -> Result<(), DeadTimeError> {
+                let dead_time = dead_time.try_into().map_err(|_| DeadTimeError::TooLong)?;
+                let ticks = u64::from(dead_time.0) * u64::from(self.clock.0) / 1_000_000_000;
+                let dtg = match ticks {
+                    0..=127 => ticks,
+                    128..=255 => 0b1000_0000 | (ticks / 2 - 64),
+                    256..=511 => 0b1100_0000 | (ticks / 8 - 32),
+                    512..=1023 => 0b1110_0000 | (ticks / 16 - 32),
+                    _ => return Err(DeadTimeError::TooLong),
+                };
+                // NOTE(unsafe): Only the DTG bits are modified.
+                unsafe {
+                    (*$TIMx::ptr())
+                        .bdtr
+                        .modify(|r, w| w.bits((r.bits() & !0xff) | dtg as u32));
+                }
+                Ok(())
+            }
+
+            /// Enables the break input at the given pin
+            ///
+            /// If the break input becomes active, all outputs of the timer
+            /// are disabled until [`rearm`](Self::rearm) is called
+            /// (or until the next update event, if the automatic output
+            /// is enabled).
+            ///
+            /// The filter value has the same meaning as for the timer inputs
+            /// (BKF bits of the TIMx_BDTR register), where `0` disables the filter.
+            ///
+            /// # Panics
+            ///
+            /// Panics, if `filter` is greater than `15`.
+            pub fn enable_break_input<P>(&mut self, _pin: P, polarity: BreakPolarity, filter: u8)
+            where
+                P: BreakPin<$TIMx>,
+            {
+                crate::assert!(filter <= 0b1111);
+                let bkp = match polarity {
+                    BreakPolarity::ActiveLow => 0,
+                    BreakPolarity::ActiveHigh => 1 << 13,
+                };
+                // NOTE(unsafe): Only the BKE, BKP and BKF bits are modified.
+                unsafe {
+                    (*$TIMx::ptr()).bdtr.modify(|r, w| {
+                        w.bits(
+                            (r.bits() & !(0b1111 << 16 | 0b11 << 12))
+                                | u32::from(filter) << 16
+                                | bkp
+                                | 1 << 12,
+                        )
+                    });
+                }
+            }
+
+            /// Disables the break input
+            pub fn disable_break_input(&mut self) {
+                // NOTE(unsafe): Only the BKE bit is modified.
+                unsafe {
+                    (*$TIMx::ptr()).bdtr.modify(|r, w| w.bits(r.bits() & !(1 << 12)));
+                }
+            }
+
+            /// Re-enables the outputs automatically on the next update event
+            /// after a break, instead of waiting for [`rearm`](Self::rearm)
+            pub fn set_automatic_output(&mut self, enable: bool) {
+                // NOTE(unsafe): Only the AOE bit is modified.
+                unsafe {
+                    (*$TIMx::ptr())
+                        .bdtr
+                        .modify(|r, w| w.bits((r.bits() & !(1 << 14)) | u32::from(enable) << 14));
+                }
+            }
+
+            /// Returns `true`, if a break event occurred since the
+            /// last call of [`rearm`](Self::rearm)
+            pub fn is_break_triggered(&self) -> bool {
+                // NOTE(unsafe) atomic read with no side effects
+                unsafe { (*$TIMx::ptr()).sr.read().bits() & (1 << 7) != 0 }
+            }
+
+            /// Clears the break event(s) and enables the outputs again
+            ///
+            /// If the break input is still active, the outputs stay disabled.
+            pub fn rearm(&mut self) {
+                // NOTE(unsafe, write): The flags are cleared by writing 0,
+                // writing 1 has no effect.
+                unsafe {
+                    (*$TIMx::ptr()).sr.write(|w| w.bits(!(0b11 << 7)));
+                    (*$TIMx::ptr()).bdtr.modify(|_, w| w.moe().set_bit());
+                }
+            }
+        }
     }
 }
 
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+macro_rules! pwm_timer_break2 {
+    ($TIMx:ident) => {
+        impl Break<$TIMx> {
+            /// Enables the second break input at the given pin
+            ///
+            /// The filter value has the same meaning as for the timer inputs
+            /// (BK2F bits of the TIMx_BDTR register), where `0` disables the filter.
+            ///
+            /// # Panics
+            ///
+            /// Panics, if `filter` is greater than `15`.
+            pub fn enable_break2_input<P>(&mut self, _pin: P, polarity: BreakPolarity, filter: u8)
+            where
+                P: Break2Pin<$TIMx>,
+            {
+                crate::assert!(filter <= 0b1111);
+                let bk2p = match polarity {
+                    BreakPolarity::ActiveLow => 0,
+                    BreakPolarity::ActiveHigh => 1 << 25,
+                };
+                // NOTE(unsafe): Only the BK2E, BK2P and BK2F bits are modified.
+                unsafe {
+                    (*$TIMx::ptr()).bdtr.modify(|r, w| {
+                        w.bits(
+                            (r.bits() & !(0b1111 << 20 | 0b11 << 24))
+                                | u32::from(filter) << 20
+                                | bk2p
+                                | 1 << 24,
+                        )
+                    });
+                }
+            }
+
+            /// Disables the second break input
+            pub fn disable_break2_input(&mut self) {
+                // NOTE(unsafe): Only the BK2E bit is modified.
+                unsafe {
+                    (*$TIMx::ptr())
+                        .bdtr
+                        .modify(|r, w| w.bits(r.bits() & !(1 << 24)));
+                }
+            }
+
+            /// Returns `true`, if a break event of the second break input
+            /// occurred since the last call of [`rearm`](Self::rearm)
+            pub fn is_break2_triggered(&self) -> bool {
+                // NOTE(unsafe) atomic read with no side effects
+                unsafe { (*$TIMx::ptr()).sr.read().bits() & (1 << 8) != 0 }
+            }
+        }
+    };
+}
+
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+macro_rules! break_pin {
+    ($TIMx:ident, $Pin:ty) => {
+        unsafe impl BreakPin<$TIMx> for $Pin {}
+    };
+}
+
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+macro_rules! break2_pin {
+    ($TIMx:ident, $Pin:ty) => {
+        unsafe impl Break2Pin<$TIMx> for $Pin {}
+    };
+}
+
+macro_rules! pwm_timer_master {
+    ($TIMx:ident, [$($TIMx_CHy:ident),+]) => {
+        $(
+            impl<T> PwmChannel<$TIMx_CHy, T> {
+                /// Selects the source of the trigger output (TRGO) of the timer,
+                /// which is shared by all channels of this timer.
+                ///
+                /// See [`Timer::set_master_mode`](crate::timer::Timer).
+                pub fn set_master_mode(&mut self, mode: MasterMode) {
+                    // NOTE(unsafe): Only the MMS bits are modified.
+                    unsafe {
+                        (*$TIMx::ptr())
+                            .cr2
+                            .modify(|r, w| w.bits((r.bits() & !(0b111 << 4)) | mode.bits() << 4));
+                    }
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! pwm_timer_sync {
+    ($TIMx:ident, [$($TIMx_CHy:ident),+]) => {
+        pwm_timer_master!($TIMx, [$($TIMx_CHy),+]);
+
+        $(
+            impl<T> PwmChannel<$TIMx_CHy, T> {
+                /// Makes the timer, which is shared by all channels of this timer,
+                /// a slave of the `MASTER` timer.
+                ///
+                /// See [`Timer::set_slave_mode`](crate::timer::Timer).
+                pub fn set_slave_mode<MASTER>(&mut self, mode: SlaveMode)
+                where
+                    MASTER: InternalTrigger<$TIMx>,
+                {
+                    let ts = u32::from(MASTER::ITR);
+                    // NOTE(unsafe): Only the SMS and TS bits are modified.
+                    unsafe {
+                        (*$TIMx::ptr()).smcr.modify(|r, w| {
+                            w.bits((r.bits() & !(1 << 16 | 0b111 << 4 | 0b111)) | ts << 4 | mode.bits())
+                        });
+                    }
+                }
+
+                /// Disables the slave mode of the timer
+                pub fn disable_slave_mode(&mut self) {
+                    // NOTE(unsafe): Only the SMS bits are modified.
+                    unsafe {
+                        (*$TIMx::ptr())
+                            .smcr
+                            .modify(|r, w| w.bits(r.bits() & !(1 << 16 | 0b111)));
+                    }
+                }
+            }
+        )+
+    };
+}
+
 macro_rules! pwm_channel_pin {
-    ($resulting_state:ident, $TIMx:ident, $TIMx_CHy:ident, $output_to_pzx:ident, $Pin:ty, $ccmrz_output:ident, $ocym:ident, $ocype:ident) => {
+    ($resulting_state:ident, $TIMx:ident, $TIMx_CHy:ident, $output_to_pzx:ident, $Pin:ty, $ccmrz_output:ident, $ocym:ident, $ocype:ident, $ccxp:ident) => {
         impl PwmChannel<$TIMx_CHy, NoPins> {
             /// Output to a specific pin from a channel that does not yet have
             /// any pins.  This channel cannot be enabled until this method
@@ -349,21 +883,30 @@ macro_rules! pwm_channel_pin {
             ///
             /// The pin is consumed and cannot be returned.
             pub fn $output_to_pzx(self, _p: $Pin) -> PwmChannel<$TIMx_CHy, $resulting_state> {
+                let ocm = match self.mode {
+                    PwmMode::Mode1 => 0b0110,
+                    PwmMode::Mode2 => 0b0111,
+                };
                 unsafe {
                     (*$TIMx::ptr()).$ccmrz_output().modify(|_, w| {
                         w
-                            // Select PWM Mode 1 for CHy
+                            // Select the PWM Mode for CHy
                             .$ocym()
-                            .bits(0b0110)
+                            .bits(ocm)
                             // set pre-load enable so that updates to the duty cycle
                             // propagate but _not_ in the middle of a cycle.
                             .$ocype()
                             .set_bit()
                     });
+                    (*$TIMx::ptr())
+                        .ccer
+                        .modify(|_, w| w.$ccxp().bit(self.polarity == Polarity::ActiveLow));
                 }
                 PwmChannel {
                     timx_chy: PhantomData,
                     pin_status: PhantomData,
+                    mode: self.mode,
+                    polarity: self.polarity,
                 }
             }
         }
@@ -383,6 +926,8 @@ macro_rules! pwm_channel_pin {
 
 macro_rules! pwm_channel1_pin {
     ($TIMx:ident, $TIMx_CHy:ident, $output_to_pzx:ident, $Pin:ty) => {
+        unsafe impl ChannelPin<$TIMx_CHy> for $Pin {}
+
         pwm_channel_pin!(
             WithPins,
             $TIMx,
@@ -391,7 +936,8 @@ macro_rules! pwm_channel1_pin {
             $Pin,
             ccmr1_output,
             oc1m,
-            oc1pe
+            oc1pe,
+            cc1p
         );
     };
 }
@@ -406,13 +952,16 @@ macro_rules! pwm_channel1n_pin {
             $Pin,
             ccmr1_output,
             oc1m,
-            oc1pe
+            oc1pe,
+            cc1np
         );
     };
 }
 
 macro_rules! pwm_channel2_pin {
     ($TIMx:ident, $TIMx_CHy:ident, $output_to_pzx:ident, $Pin:ty) => {
+        unsafe impl ChannelPin<$TIMx_CHy> for $Pin {}
+
         pwm_channel_pin!(
             WithPins,
             $TIMx,
@@ -421,15 +970,18 @@ macro_rules! pwm_channel2_pin {
             $Pin,
             ccmr1_output,
             oc2m,
-            oc2pe
+            oc2pe,
+            cc2p
         );
     };
 }
 
 #[cfg(any(
+    feature = "stm32f301",
     feature = "stm32f318",
     feature = "stm32f302",
     feature = "stm32f303",
+    feature = "stm32f328",
     feature = "stm32f334",
     feature = "stm32f358",
     feature = "stm32f398"
@@ -444,13 +996,16 @@ macro_rules! pwm_channel2n_pin {
             $Pin,
             ccmr1_output,
             oc2m,
-            oc2pe
+            oc2pe,
+            cc2np
         );
     };
 }
 
 macro_rules! pwm_channel3_pin {
     ($TIMx:ident, $TIMx_CHy:ident, $output_to_pzx:ident, $Pin:ty) => {
+        unsafe impl ChannelPin<$TIMx_CHy> for $Pin {}
+
         pwm_channel_pin!(
             WithPins,
             $TIMx,
@@ -459,15 +1014,18 @@ macro_rules! pwm_channel3_pin {
             $Pin,
             ccmr2_output,
             oc3m,
-            oc3pe
+            oc3pe,
+            cc3p
         );
     };
 }
 
 #[cfg(any(
+    feature = "stm32f301",
     feature = "stm32f318",
     feature = "stm32f302",
     feature = "stm32f303",
+    feature = "stm32f328",
     feature = "stm32f334",
     feature = "stm32f358",
     feature = "stm32f398"
@@ -482,13 +1040,16 @@ macro_rules! pwm_channel3n_pin {
             $Pin,
             ccmr2_output,
             oc3m,
-            oc3pe
+            oc3pe,
+            cc3np
         );
     };
 }
 
 macro_rules! pwm_channel4_pin {
     ($TIMx:ident, $TIMx_CHy:ident, $output_to_pzx:ident, $Pin:ty) => {
+        unsafe impl ChannelPin<$TIMx_CHy> for $Pin {}
+
         pwm_channel_pin!(
             WithPins,
             $TIMx,
@@ -497,7 +1058,8 @@ macro_rules! pwm_channel4_pin {
             $Pin,
             ccmr2_output,
             oc4m,
-            oc4pe
+            oc4pe,
+            cc4p
         );
     };
 }
@@ -557,9 +1119,11 @@ macro_rules! pwm_pin_for_pwm_n_channel {
 // TIM1
 
 #[cfg(any(
+    feature = "stm32f301",
     feature = "stm32f318",
     feature = "stm32f302",
     feature = "stm32f303",
+    feature = "stm32f328",
     feature = "stm32f334",
     feature = "stm32f358",
     feature = "stm32f398"
@@ -583,7 +1147,7 @@ macro_rules! tim1_common {
             u16,
             apb2enr,
             apb2rstr,
-            pclk2,
+            timclk2,
             tim1rst,
             tim1en,
             [TIM1_CH1, TIM1_CH2, TIM1_CH3, TIM1_CH4],
@@ -595,6 +1159,7 @@ macro_rules! tim1_common {
         pwm_pin_for_pwm_n_channel!(TIM1, TIM1_CH2, u16, cc2e, cc2ne, ccr2, ccr);
         pwm_pin_for_pwm_n_channel!(TIM1, TIM1_CH3, u16, cc3e, cc3ne, ccr3, ccr);
         pwm_pin_for_pwm_channel!(TIM1, TIM1_CH4, u16, cc4e, ccr4, ccr);
+        pwm_timer_sync!(TIM1, [TIM1_CH1, TIM1_CH2, TIM1_CH3, TIM1_CH4]);
 
         //Pins
         pwm_channel1_pin!(TIM1, TIM1_CH1, output_to_pa8, gpioa::PA8<gpio::AF6>);
@@ -617,6 +1182,15 @@ macro_rules! tim1_common {
         pwm_channel3n_pin!(TIM1, TIM1_CH3, output_to_pf0, gpiof::PF0<gpio::AF6>);
 
         pwm_channel4_pin!(TIM1, TIM1_CH4, output_to_pa11, gpioa::PA11<gpio::AF11>);
+
+        // Break inputs
+        pwm_timer_break2!(TIM1);
+
+        break_pin!(TIM1, gpioa::PA6<gpio::AF6>);
+        break_pin!(TIM1, gpiob::PB8<gpio::AF12>);
+        break_pin!(TIM1, gpiob::PB12<gpio::AF6>);
+
+        break2_pin!(TIM1, gpioa::PA11<gpio::AF12>);
     };
 }
 
@@ -660,14 +1234,20 @@ macro_rules! tim1_ext2 {
         pwm_channel3n_pin!(TIM1, TIM1_CH3, output_to_pe12, gpioe::PE12<gpio::AF2>);
 
         pwm_channel4_pin!(TIM1, TIM1_CH4, output_to_pe14, gpioe::PE14<gpio::AF2>);
+
+        break_pin!(TIM1, gpioe::PE15<gpio::AF2>);
+
+        break2_pin!(TIM1, gpioe::PE14<gpio::AF6>);
     };
 }
 
 // TODO: stm32f301 has TIM1 with ext1
 #[cfg(any(
+    feature = "stm32f301",
     feature = "stm32f318",
     feature = "stm32f302",
     feature = "stm32f303",
+    feature = "stm32f328",
     feature = "stm32f334",
     feature = "stm32f358",
     feature = "stm32f398"
@@ -699,7 +1279,7 @@ pwm_timer_basic!(
     u32,
     apb1enr,
     apb1rstr,
-    pclk1,
+    timclk1,
     tim2rst,
     tim2en,
     [TIM2_CH1, TIM2_CH2, TIM2_CH3, TIM2_CH4],
@@ -712,6 +1292,8 @@ pwm_pin_for_pwm_channel!(TIM2, TIM2_CH2, u32, cc2e, ccr2, ccr);
 pwm_pin_for_pwm_channel!(TIM2, TIM2_CH3, u32, cc3e, ccr3, ccr);
 pwm_pin_for_pwm_channel!(TIM2, TIM2_CH4, u32, cc4e, ccr4, ccr);
 
+pwm_timer_sync!(TIM2, [TIM2_CH1, TIM2_CH2, TIM2_CH3, TIM2_CH4]);
+
 // Pins
 pwm_channel1_pin!(TIM2, TIM2_CH1, output_to_pa0, gpioa::PA0<gpio::AF1>);
 pwm_channel1_pin!(TIM2, TIM2_CH1, output_to_pa5, gpioa::PA5<gpio::AF1>);
@@ -812,7 +1394,7 @@ macro_rules! tim3_common {
             u16,
             apb1enr,
             apb1rstr,
-            pclk1,
+            timclk1,
             tim3rst,
             tim3en,
             [TIM3_CH1, TIM3_CH2, TIM3_CH3, TIM3_CH4],
@@ -824,6 +1406,7 @@ macro_rules! tim3_common {
         pwm_pin_for_pwm_channel!(TIM3, TIM3_CH2, u16, cc2e, ccr2, ccr);
         pwm_pin_for_pwm_channel!(TIM3, TIM3_CH3, u16, cc3e, ccr3, ccr);
         pwm_pin_for_pwm_channel!(TIM3, TIM3_CH4, u16, cc4e, ccr4, ccr);
+        pwm_timer_sync!(TIM3, [TIM3_CH1, TIM3_CH2, TIM3_CH3, TIM3_CH4]);
 
         // Pins
         pwm_channel1_pin!(TIM3, TIM3_CH1, output_to_pa6, gpioa::PA6<gpio::AF2>);
@@ -957,7 +1540,7 @@ macro_rules! tim4_common {
             u16,
             apb1enr,
             apb1rstr,
-            pclk1,
+            timclk1,
             tim4rst,
             tim4en,
             [TIM4_CH1, TIM4_CH2, TIM4_CH3, TIM4_CH4],
@@ -969,6 +1552,7 @@ macro_rules! tim4_common {
         pwm_pin_for_pwm_channel!(TIM4, TIM4_CH2, u16, cc2e, ccr2, ccr);
         pwm_pin_for_pwm_channel!(TIM4, TIM4_CH3, u16, cc3e, ccr3, ccr);
         pwm_pin_for_pwm_channel!(TIM4, TIM4_CH4, u16, cc4e, ccr4, ccr);
+        pwm_timer_sync!(TIM4, [TIM4_CH1, TIM4_CH2, TIM4_CH3, TIM4_CH4]);
 
         // Pins
         pwm_channel1_pin!(TIM4, TIM4_CH1, output_to_pa11, gpioa::PA11<gpio::AF10>);
@@ -1059,7 +1643,7 @@ macro_rules! tim5 {
             u32,
             apb1enr,
             apb1rstr,
-            pclk1,
+            timclk1,
             tim5rst,
             tim5en,
             [TIM5_CH1, TIM5_CH2, TIM5_CH3, TIM5_CH4],
@@ -1071,6 +1655,7 @@ macro_rules! tim5 {
         pwm_pin_for_pwm_channel!(TIM5, TIM5_CH2, u32, cc2e, ccr2, ccr);
         pwm_pin_for_pwm_channel!(TIM5, TIM5_CH3, u32, cc3e, ccr3, ccr);
         pwm_pin_for_pwm_channel!(TIM5, TIM5_CH4, u32, cc4e, ccr4, ccr);
+        pwm_timer_sync!(TIM5, [TIM5_CH1, TIM5_CH2, TIM5_CH3, TIM5_CH4]);
 
         // Pins
         pwm_channel1_pin!(TIM5, TIM5_CH1, output_to_pa0, gpioa::PA0<gpio::AF2>);
@@ -1117,7 +1702,7 @@ macro_rules! tim8 {
             u16,
             apb2enr,
             apb2rstr,
-            pclk2,
+            timclk2,
             tim8rst,
             tim8en,
             [TIM8_CH1, TIM8_CH2, TIM8_CH3, TIM8_CH4],
@@ -1129,6 +1714,7 @@ macro_rules! tim8 {
         pwm_pin_for_pwm_n_channel!(TIM8, TIM8_CH2, u16, cc2e, cc2ne, ccr2, ccr);
         pwm_pin_for_pwm_n_channel!(TIM8, TIM8_CH3, u16, cc3e, cc3ne, ccr3, ccr);
         pwm_pin_for_pwm_channel!(TIM8, TIM8_CH4, u16, cc4e, ccr4, ccr);
+        pwm_timer_sync!(TIM8, [TIM8_CH1, TIM8_CH2, TIM8_CH3, TIM8_CH4]);
 
         //Pins
         pwm_channel1_pin!(TIM8, TIM8_CH1, output_to_pa15, gpioa::PA15<gpio::AF2>);
@@ -1155,6 +1741,17 @@ macro_rules! tim8 {
         pwm_channel3n_pin!(TIM8, TIM8_CH3, output_to_pc12, gpioc::PC12<gpio::AF4>);
 
         pwm_channel4_pin!(TIM8, TIM8_CH4, output_to_pc9, gpioc::PC9<gpio::AF4>);
+
+        // Break inputs
+        pwm_timer_break2!(TIM8);
+
+        break_pin!(TIM8, gpioa::PA0<gpio::AF9>);
+        break_pin!(TIM8, gpioa::PA6<gpio::AF4>);
+        break_pin!(TIM8, gpioa::PA10<gpio::AF11>);
+        break_pin!(TIM8, gpiob::PB7<gpio::AF5>);
+
+        break2_pin!(TIM8, gpiob::PB6<gpio::AF10>);
+        break2_pin!(TIM8, gpioc::PC9<gpio::AF6>);
     };
 }
 
@@ -1171,6 +1768,16 @@ tim8!();
 ))]
 pwm_channel4_pin!(TIM8, TIM8_CH4, output_to_pd1, gpiod::PD1<gpio::AF4>);
 
+#[cfg(any(
+    feature = "stm32f303xb",
+    feature = "stm32f303xc",
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+break2_pin!(TIM8, gpiod::PD1<gpio::AF6>);
+
 // TIM12
 
 #[cfg(feature = "stm32f373")]
@@ -1193,7 +1800,7 @@ macro_rules! tim12 {
             u16,
             apb1enr,
             apb1rstr,
-            pclk1,
+            timclk1,
             tim12rst,
             tim12en,
             [TIM12_CH1, TIM12_CH2],
@@ -1241,7 +1848,7 @@ macro_rules! tim13 {
             u16,
             apb1enr,
             apb1rstr,
-            pclk1,
+            timclk1,
             tim13rst,
             tim13en,
             [TIM13_CH1],
@@ -1284,7 +1891,7 @@ macro_rules! tim14 {
             u16,
             apb1enr,
             apb1rstr,
-            pclk1,
+            timclk1,
             tim14rst,
             tim14en,
             [TIM14_CH1],
@@ -1314,7 +1921,7 @@ pwm_timer_with_break!(
     u16,
     apb2enr,
     apb2rstr,
-    pclk2,
+    timclk2,
     tim15rst,
     tim15en,
     [TIM15_CH1, TIM15_CH2],
@@ -1325,6 +1932,8 @@ pwm_timer_with_break!(
 pwm_pin_for_pwm_n_channel!(TIM15, TIM15_CH1, u16, cc1e, cc1ne, ccr1, ccr1);
 pwm_pin_for_pwm_channel!(TIM15, TIM15_CH2, u16, cc2e, ccr2, ccr2);
 
+pwm_timer_sync!(TIM15, [TIM15_CH1, TIM15_CH2]);
+
 // Pins
 pwm_channel1_pin!(TIM15, TIM15_CH1, output_to_pa2, gpioa::PA2<gpio::AF9>);
 #[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
@@ -1364,6 +1973,10 @@ pwm_channel2_pin!(TIM15, TIM15_CH2, output_to_pb15, gpiob::PB15<gpio::AF2>);
 ))]
 pwm_channel2_pin!(TIM15, TIM15_CH2, output_to_pf10, gpiof::PF10<gpio::AF3>);
 
+// Break inputs
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+break_pin!(TIM15, gpioa::PA9<gpio::AF9>);
+
 // TIM16
 
 pwm_timer_with_break!(
@@ -1372,7 +1985,7 @@ pwm_timer_with_break!(
     u16,
     apb2enr,
     apb2rstr,
-    pclk2,
+    timclk2,
     tim16rst,
     tim16en,
     [TIM16_CH1],
@@ -1404,6 +2017,10 @@ pwm_channel1_pin!(TIM16, TIM16_CH1, output_to_pe0, gpioe::PE0<gpio::AF4>);
 pwm_channel1n_pin!(TIM16, TIM16_CH1, output_to_pa13, gpioa::PA13<gpio::AF1>);
 pwm_channel1n_pin!(TIM16, TIM16_CH1, output_to_pb6, gpiob::PB6<gpio::AF1>);
 
+// Break inputs
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+break_pin!(TIM16, gpiob::PB5<gpio::AF1>);
+
 // TIM17
 
 pwm_timer_with_break!(
@@ -1412,7 +2029,7 @@ pwm_timer_with_break!(
     u16,
     apb2enr,
     apb2rstr,
-    pclk2,
+    timclk2,
     tim17rst,
     tim17en,
     [TIM17_CH1],
@@ -1442,6 +2059,12 @@ pwm_channel1_pin!(TIM17, TIM17_CH1, output_to_pe1, gpioe::PE1<gpio::AF4>);
 
 pwm_channel1n_pin!(TIM17, TIM17_CH1, output_to_pa13, gpioa::PA13<gpio::AF1>);
 
+// Break inputs
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+break_pin!(TIM17, gpioa::PA10<gpio::AF1>);
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+break_pin!(TIM17, gpiob::PB4<gpio::AF10>);
+
 // TIM19
 
 #[cfg(feature = "stm32f373")]
@@ -1464,7 +2087,7 @@ macro_rules! tim19 {
             u16,
             apb2enr,
             apb2rstr,
-            pclk2,
+            timclk2,
             tim19rst,
             tim19en,
             [TIM19_CH1, TIM19_CH2, TIM19_CH3, TIM19_CH4],
@@ -1476,6 +2099,7 @@ macro_rules! tim19 {
         pwm_pin_for_pwm_channel!(TIM19, TIM19_CH2, u16, cc2e, ccr2, ccr);
         pwm_pin_for_pwm_channel!(TIM19, TIM19_CH3, u16, cc3e, ccr3, ccr);
         pwm_pin_for_pwm_channel!(TIM19, TIM19_CH4, u16, cc4e, ccr4, ccr);
+        pwm_timer_sync!(TIM19, [TIM19_CH1, TIM19_CH2, TIM19_CH3, TIM19_CH4]);
 
         // Pins
         pwm_channel1_pin!(TIM19, TIM19_CH1, output_to_pa0, gpioa::PA0<gpio::AF11>);
@@ -1502,7 +2126,13 @@ tim19!();
 
 // TIM20
 //
-#[cfg(feature = "stm32f398")]
+// The stm32f3x8 PAC models TIM20 like TIM16, so only channel 1 is supported
+// on the stm32f398.
+#[cfg(any(
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+    feature = "stm32f398"
+))]
 macro_rules! tim20 {
     () => {
         use crate::pac::TIM20;
@@ -1516,29 +2146,59 @@ macro_rules! tim20 {
         /// Output Compare Channel 4 of Timer 20 (type state)
         pub struct TIM20_CH4 {}
 
-        pwm_timer_basic!(
+        pwm_timer_with_break!(
             tim20,
             TIM20,
             u16,
             apb2enr,
             apb2rstr,
-            pclk2,
+            timclk2,
             tim20rst,
             tim20en,
             [TIM20_CH1, TIM20_CH2, TIM20_CH3, TIM20_CH4],
             [PwmChannel, PwmChannel, PwmChannel, PwmChannel]
         );
+        pwm_timer_master!(TIM20, [TIM20_CH1, TIM20_CH2, TIM20_CH3, TIM20_CH4]);
 
         // Channels
-        // TODO: stm32f3 doesn't suppport registers for all 4 channels
+        #[cfg(feature = "stm32f398")]
         pwm_pin_for_pwm_n_channel!(TIM20, TIM20_CH1, u16, cc1e, cc1ne, ccr1, ccr1);
+        #[cfg(not(feature = "stm32f398"))]
+        pwm_pin_for_pwm_n_channel!(TIM20, TIM20_CH1, u16, cc1e, cc1ne, ccr1, ccr);
+        #[cfg(not(feature = "stm32f398"))]
+        pwm_pin_for_pwm_n_channel!(TIM20, TIM20_CH2, u16, cc2e, cc2ne, ccr2, ccr);
+        #[cfg(not(feature = "stm32f398"))]
+        pwm_pin_for_pwm_n_channel!(TIM20, TIM20_CH3, u16, cc3e, cc3ne, ccr3, ccr);
+        #[cfg(not(feature = "stm32f398"))]
+        pwm_pin_for_pwm_channel!(TIM20, TIM20_CH4, u16, cc4e, ccr4, ccr);
 
         //Pins
         pwm_channel1_pin!(TIM20, TIM20_CH1, output_to_pe2, gpioe::PE2<gpio::AF6>);
+        pwm_channel1_pin!(TIM20, TIM20_CH1, output_to_pf12, gpiof::PF12<gpio::AF2>);
 
         pwm_channel1n_pin!(TIM20, TIM20_CH1, output_to_pe4, gpioe::PE4<gpio::AF6>);
+
+        #[cfg(not(feature = "stm32f398"))]
+        pwm_channel2_pin!(TIM20, TIM20_CH2, output_to_pe3, gpioe::PE3<gpio::AF6>);
+        #[cfg(not(feature = "stm32f398"))]
+        pwm_channel2_pin!(TIM20, TIM20_CH2, output_to_pf13, gpiof::PF13<gpio::AF2>);
+
+        // Break inputs
+        pwm_timer_break2!(TIM20);
+
+        break_pin!(TIM20, gpiof::PF7<gpio::AF2>);
+        break_pin!(TIM20, gpiof::PF9<gpio::AF2>);
+        break_pin!(TIM20, gpiog::PG3<gpio::AF2>);
+
+        break2_pin!(TIM20, gpiof::PF8<gpio::AF2>);
+        break2_pin!(TIM20, gpiof::PF10<gpio::AF2>);
+        break2_pin!(TIM20, gpiog::PG4<gpio::AF2>);
     };
 }
 
-#[cfg(feature = "stm32f398")]
+#[cfg(any(
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+    feature = "stm32f398"
+))]
 tim20!();
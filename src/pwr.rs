@@ -0,0 +1,109 @@
+//! Power control and low-power modes
+//!
+//! The core can be put into the Sleep mode, in which only the CPU clock is
+//! stopped, or into the Stop mode, in which all clocks of the 1.8 V domain are
+//! stopped and the PLL, the HSI and the HSE are disabled. The SRAM and the
+//! registers keep their content in both modes. See STM32F303 reference manual,
+//! section 4.3.
+//!
+//! The MCU wakes up from Stop mode on any EXTI line, e.g. on a GPIO edge
+//! configured with [`ExtiPin`](crate::gpio::ExtiPin) or on the activity of a
+//! USART armed with `Serial::enable_wakeup_from_stop`.
+//!
+//! ```
+//! let mut pwr = Pwr::new(dp.PWR, &mut rcc.apb1);
+//! let mut cp = cortex_m::Peripherals::take().unwrap();
+//!
+//! // Sleep until an interrupt wakes the MCU up
+//! pwr.enter_stop(&mut cp.SCB, Regulator::LowPower);
+//! ```
+
+use crate::pac::{PWR, RCC};
+use crate::rcc::APB1;
+use cortex_m::{asm, interrupt, peripheral::SCB};
+
+/// Mode of the voltage regulator in Stop mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Regulator {
+    /// The regulator stays in the main mode, which wakes up faster
+    Main,
+    /// The regulator switches to the low-power mode, which consumes less power
+    /// but increases the wake-up time
+    LowPower,
+}
+
+/// Power control
+pub struct Pwr {
+    /// PWR Peripheral register definition
+    pub regs: PWR,
+}
+
+impl Pwr {
+    /// Enables the clock of the power control
+    ///
+    /// The PWR peripheral can still be accessed via [`regs`](Self::regs),
+    /// e.g. to configure the [`Rtc`](crate::rtc::Rtc).
+    pub fn new(regs: PWR, apb1: &mut APB1) -> Self {
+        apb1.enr().modify(|_, w| w.pwren().set_bit());
+        Pwr { regs }
+    }
+
+    /// Enters the Sleep mode and waits for an interrupt
+    ///
+    /// Only the CPU clock is stopped, so all peripherals keep running
+    /// and any interrupt wakes the core up.
+    pub fn enter_sleep(&mut self, scb: &mut SCB) {
+        scb.clear_sleepdeep();
+        asm::wfi();
+    }
+
+    /// Enters the Stop mode and waits for an interrupt
+    ///
+    /// The MCU is woken up by the interrupt of an EXTI line, which has to be
+    /// unmasked in the EXTI and in the NVIC. Pending interrupts or EXTI
+    /// flags prevent the Stop mode, so they have to be cleared beforehand.
+    ///
+    /// After the wake-up, the HSE and the PLL are enabled again and the system
+    /// clock is switched back to the source selected before entering the Stop mode,
+    /// so the [`Clocks`](crate::rcc::Clocks) stay valid. The interrupt, which
+    /// woke the MCU up, is handled after the clocks are restored.
+    pub fn enter_stop(&mut self, scb: &mut SCB, regulator: Regulator) {
+        self.regs.cr.modify(|_, w| {
+            w.pdds().clear_bit();
+            w.lpds().bit(regulator == Regulator::LowPower)
+        });
+
+        // The interrupts are masked, so the interrupt waking the MCU up is only
+        // handled after the clocks are restored. WFI wakes up nevertheless.
+        interrupt::free(|_| {
+            // NOTE(unsafe) the clocks are configured once by `CFGR::freeze`,
+            // so this only restores the state of the registers after the wake-up
+            let rcc = unsafe { &*RCC::ptr() };
+            let cr = rcc.cr.read();
+            let sw = rcc.cfgr.read().sw().bits();
+
+            scb.set_sleepdeep();
+            asm::wfi();
+            scb.clear_sleepdeep();
+
+            if cr.hseon().is_on() {
+                rcc.cr.modify(|_, w| w.hseon().on());
+                while rcc.cr.read().hserdy().is_not_ready() {}
+            }
+
+            if cr.pllon().is_on() {
+                rcc.cr.modify(|_, w| w.pllon().on());
+                while rcc.cr.read().pllrdy().is_not_ready() {}
+            }
+
+            // NOTE(unsafe) the value has been read from this field
+            rcc.cfgr.modify(|_, w| unsafe { w.sw().bits(sw) });
+            while rcc.cfgr.read().sws().bits() != sw {}
+        });
+    }
+
+    /// Releases the PWR peripheral
+    pub fn release(self) -> PWR {
+        self.regs
+    }
+}
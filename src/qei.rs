@@ -0,0 +1,292 @@
+//! Quadrature encoder interface
+//!
+//! The general purpose and advanced timers can decode the signals of an
+//! incremental (quadrature) encoder in hardware. The two encoder signals are
+//! connected to channel 1 and channel 2 of the timer and the counter of the
+//! timer follows the position of the encoder.
+//!
+//! The basic timers (TIM6, TIM7) and the timers with less than two channels
+//! or without a slave mode controller supporting the encoder modes
+//! (TIM12 to TIM18) are not supported.
+//!
+//! TIM20 is only supported on the STM32F303xD/E. The PAC models TIM20 of the
+//! STM32F398 like TIM16 without a slave mode controller, and the smaller STM32F303
+//! parts do not have a TIM20. The STM32F328 has no TIM4 and TIM8 either, as it is
+//! the STM32F303x8 without the internal voltage regulator.
+//!
+//! ```
+//! let mut gpioa = dp.GPIOA.split(&mut rcc.ahb);
+//! let pa0 = gpioa.pa0.into_af1(&mut gpioa.moder, &mut gpioa.afrl);
+//! let pa1 = gpioa.pa1.into_af1(&mut gpioa.moder, &mut gpioa.afrl);
+//!
+//! let qei = Qei::tim2(dp.TIM2, (pa0, pa1), Mode::Ti1AndTi2, &mut rcc.apb1);
+//! let position = qei.count();
+//! ```
+
+#[cfg(feature = "unproven")]
+use crate::hal::{self, Direction};
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+use crate::pac::TIM1;
+use crate::pac::TIM2;
+#[cfg(any(feature = "stm32f303xd", feature = "stm32f303xe"))]
+use crate::pac::TIM20;
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f334",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+use crate::pac::TIM3;
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+use crate::pac::TIM4;
+#[cfg(any(feature = "stm32f303", feature = "stm32f358", feature = "stm32f398"))]
+use crate::pac::TIM8;
+#[cfg(feature = "stm32f373")]
+use crate::pac::{TIM19, TIM5};
+use crate::pwm::ChannelPin;
+use crate::rcc::APB1;
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f373",
+    feature = "stm32f398"
+))]
+use crate::rcc::APB2;
+
+/// Encoder mode, which selects on which edges the counter counts
+pub enum Mode {
+    /// Count on the edges of TI2, depending on the level of TI1
+    /// (encoder mode 1)
+    Ti2,
+    /// Count on the edges of TI1, depending on the level of TI2
+    /// (encoder mode 2)
+    Ti1,
+    /// Count on the edges of both TI1 and TI2 (encoder mode 3)
+    Ti1AndTi2,
+}
+
+impl Mode {
+    /// Value of the SMS bits of the TIMx_SMCR register
+    fn sms(&self) -> u32 {
+        match self {
+            Mode::Ti2 => 0b001,
+            Mode::Ti1 => 0b010,
+            Mode::Ti1AndTi2 => 0b011,
+        }
+    }
+}
+
+/// Quadrature encoder interface
+///
+/// Owns the timer and the two pins, which are connected
+/// to channel 1 and channel 2 of the timer.
+pub struct Qei<TIM, PINS> {
+    tim: TIM,
+    pins: PINS,
+}
+
+macro_rules! qei {
+    ($({
+        $TIMX:ident: ($tim:ident, $timXen:ident, $timXrst:ident, $count:ty),
+        $APB:ident: $apb:ident,
+        pins: ($CH1:ident, $CH2:ident),
+    },)+) => {
+        $(
+            impl<CH1, CH2> Qei<$TIMX, (CH1, CH2)> {
+                /// Configures a TIM peripheral as a quadrature encoder interface
+                pub fn $tim(tim: $TIMX, pins: (CH1, CH2), mode: Mode, $apb: &mut $APB) -> Self
+                where
+                    CH1: ChannelPin<crate::pwm::$CH1>,
+                    CH2: ChannelPin<crate::pwm::$CH2>,
+                {
+                    // enable and reset peripheral to a clean slate state
+                    $apb.enr().modify(|_, w| w.$timXen().enabled());
+                    $apb.rstr().modify(|_, w| w.$timXrst().reset());
+                    $apb.rstr().modify(|_, w| w.$timXrst().clear_bit());
+
+                    // Map channel 1 to TI1 and channel 2 to TI2 (CCxS = 0b01)
+                    // NOTE(unsafe): the timer is owned by this struct
+                    tim.ccmr1_input()
+                        .modify(|r, w| unsafe { w.bits((r.bits() & !0x0303) | 0x0101) });
+
+                    // Both inputs are not inverted
+                    // NOTE(unsafe): the timer is owned by this struct
+                    tim.ccer.modify(|r, w| unsafe { w.bits(r.bits() & !0xaa) });
+
+                    // Select the encoder mode
+                    // NOTE(unsafe): the timer is owned by this struct
+                    tim.smcr
+                        .modify(|r, w| unsafe { w.bits((r.bits() & !0x1_0007) | mode.sms()) });
+
+                    // Count through the whole range of the counter
+                    // NOTE(write): uses all bits of this register.
+                    tim.arr.write(|w| unsafe { w.bits(u32::from(<$count>::max_value())) });
+
+                    // Enable the counter
+                    tim.cr1.modify(|_, w| w.cen().enabled());
+
+                    Qei { tim, pins }
+                }
+
+                /// Sets the digital filter of both encoder inputs
+                ///
+                /// The filter value selects the sampling frequency and the number of
+                /// consecutive samples needed to validate an edge. `0` disables the
+                /// filter and `15` is the strongest filter. See the reference manual
+                /// (ICxF bits of the TIMx_CCMRx register) for the exact meaning of the values.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if `filter` is greater than `15`.
+                pub fn set_filter(&mut self, filter: u8) {
+                    crate::assert!(filter <= 0b1111);
+                    let filter = u32::from(filter);
+                    // NOTE(unsafe): the timer is owned by this struct
+                    self.tim.ccmr1_input().modify(|r, w| unsafe {
+                        w.bits((r.bits() & !0xf0f0) | (filter << 4) | (filter << 12))
+                    });
+                }
+
+                /// Releases the TIM peripheral and the pins
+                pub fn release(self) -> ($TIMX, (CH1, CH2)) {
+                    self.tim.cr1.modify(|_, w| w.cen().disabled());
+                    (self.tim, self.pins)
+                }
+            }
+
+            #[cfg(feature = "unproven")]
+            impl<CH1, CH2> hal::Qei for Qei<$TIMX, (CH1, CH2)> {
+                type Count = $count;
+
+                fn count(&self) -> $count {
+                    self.tim.cnt.read().bits() as $count
+                }
+
+                fn direction(&self) -> Direction {
+                    if self.tim.cr1.read().dir().bit_is_clear() {
+                        Direction::Upcounting
+                    } else {
+                        Direction::Downcounting
+                    }
+                }
+            }
+        )+
+    }
+}
+
+#[cfg(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+qei! {
+    {
+        TIM1: (tim1, tim1en, tim1rst, u16),
+        APB2: apb2,
+        pins: (TIM1_CH1, TIM1_CH2),
+    },
+}
+
+qei! {
+    {
+        TIM2: (tim2, tim2en, tim2rst, u32),
+        APB1: apb1,
+        pins: (TIM2_CH1, TIM2_CH2),
+    },
+}
+
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f334",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+qei! {
+    {
+        TIM3: (tim3, tim3en, tim3rst, u16),
+        APB1: apb1,
+        pins: (TIM3_CH1, TIM3_CH2),
+    },
+}
+
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+qei! {
+    {
+        TIM4: (tim4, tim4en, tim4rst, u16),
+        APB1: apb1,
+        pins: (TIM4_CH1, TIM4_CH2),
+    },
+}
+
+#[cfg(any(feature = "stm32f303", feature = "stm32f358", feature = "stm32f398"))]
+qei! {
+    {
+        TIM8: (tim8, tim8en, tim8rst, u16),
+        APB2: apb2,
+        pins: (TIM8_CH1, TIM8_CH2),
+    },
+}
+
+#[cfg(feature = "stm32f373")]
+qei! {
+    {
+        TIM5: (tim5, tim5en, tim5rst, u32),
+        APB1: apb1,
+        pins: (TIM5_CH1, TIM5_CH2),
+    },
+    {
+        TIM19: (tim19, tim19en, tim19rst, u16),
+        APB2: apb2,
+        pins: (TIM19_CH1, TIM19_CH2),
+    },
+}
+
+#[cfg(any(feature = "stm32f303xd", feature = "stm32f303xe"))]
+qei! {
+    {
+        TIM20: (tim20, tim20en, tim20rst, u16),
+        APB2: apb2,
+        pins: (TIM20_CH1, TIM20_CH2),
+    },
+}
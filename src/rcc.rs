@@ -179,6 +179,7 @@ impl APB2 {
 }
 
 const HSI: u32 = 8_000_000; // Hz
+const LSE: u32 = 32_768; // Hz
 
 // some microcontrollers do not have USB
 #[cfg(any(feature = "stm32f301", feature = "stm32f318", feature = "stm32f334",))]
@@ -245,6 +246,48 @@ impl BDCR {
     }
 }
 
+/// Clock source of a USART
+///
+/// The USART derives its baud rate from this clock. Clocking a USART
+/// independently of the APB prescalers keeps the baud rate accurate
+/// when changing the APB prescalers. With `HSI` or `LSE` the USART can
+/// also keep receiving in Stop mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UsartClockSource {
+    /// The clock of the APB bus, the USART is connected to
+    Pclk,
+    /// The system clock
+    Sysclk,
+    /// The external low-speed oscillator (32.768 kHz)
+    ///
+    /// `LSE` is not enabled by the clock configuration, but e.g. by the RTC.
+    Lse,
+    /// The internal high-speed oscillator (8 MHz)
+    Hsi,
+}
+
+impl UsartClockSource {
+    /// Frequency of the USART clock, where `pclk` is the frequency of the APB bus
+    fn frequency(self, pclk: u32, sysclk: u32) -> u32 {
+        match self {
+            UsartClockSource::Pclk => pclk,
+            UsartClockSource::Sysclk => sysclk,
+            UsartClockSource::Lse => LSE,
+            UsartClockSource::Hsi => HSI,
+        }
+    }
+
+    /// Value of the USARTxSW bits of the RCC_CFGR3 register
+    fn bits(self) -> u8 {
+        match self {
+            UsartClockSource::Pclk => 0b00,
+            UsartClockSource::Sysclk => 0b01,
+            UsartClockSource::Lse => 0b10,
+            UsartClockSource::Hsi => 0b11,
+        }
+    }
+}
+
 /// Clock configuration
 ///
 /// An instance of this struct is acquired from the [`RCC`](crate::pac::RCC) struct.
@@ -263,6 +306,35 @@ pub struct CFGR {
     pclk1: Option<u32>,
     pclk2: Option<u32>,
     sysclk: Option<u32>,
+    usart1_source: Option<UsartClockSource>,
+    usart2_source: Option<UsartClockSource>,
+    usart3_source: Option<UsartClockSource>,
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    uart4_source: Option<UsartClockSource>,
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    uart5_source: Option<UsartClockSource>,
 }
 
 pub(crate) struct PllConfig {
@@ -430,6 +502,86 @@ impl CFGR {
         self
     }
 
+    /// Sets the clock source of USART1
+    ///
+    /// If not manually set, USART1 is clocked by the `APB2` bus.
+    pub fn usart1_clock(mut self, source: UsartClockSource) -> Self {
+        self.usart1_source = Some(source);
+        self
+    }
+
+    /// Sets the clock source of USART2
+    ///
+    /// If not manually set, USART2 is clocked by the `APB1` bus.
+    #[cfg(any(
+        feature = "stm32f302",
+        feature = "stm32f303",
+        feature = "stm32f373",
+        feature = "stm32f378",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    pub fn usart2_clock(mut self, source: UsartClockSource) -> Self {
+        self.usart2_source = Some(source);
+        self
+    }
+
+    /// Sets the clock source of USART3
+    ///
+    /// If not manually set, USART3 is clocked by the `APB1` bus.
+    #[cfg(any(
+        feature = "stm32f302",
+        feature = "stm32f303",
+        feature = "stm32f373",
+        feature = "stm32f378",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    pub fn usart3_clock(mut self, source: UsartClockSource) -> Self {
+        self.usart3_source = Some(source);
+        self
+    }
+
+    /// Sets the clock source of UART4
+    ///
+    /// If not manually set, UART4 is clocked by the `APB1` bus.
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    pub fn uart4_clock(mut self, source: UsartClockSource) -> Self {
+        self.uart4_source = Some(source);
+        self
+    }
+
+    /// Sets the clock source of UART5
+    ///
+    /// If not manually set, UART5 is clocked by the `APB1` bus.
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    pub fn uart5_clock(mut self, source: UsartClockSource) -> Self {
+        self.uart5_source = Some(source);
+        self
+    }
+
     /// Calculate the values for the pll multiplier (`PLLMUL`) and the pll divisior (`PLLDIV`).
     ///
     /// These values are chosen depending on the chosen system clock (SYSCLK) and the frequency of the
@@ -744,6 +896,42 @@ impl CFGR {
                 .variant(sysclk_source)
         });
 
+        // set the clock sources of the USARTs
+        let usart1_source = self.usart1_source.unwrap_or(UsartClockSource::Pclk);
+        let usart2_source = self.usart2_source.unwrap_or(UsartClockSource::Pclk);
+        let usart3_source = self.usart3_source.unwrap_or(UsartClockSource::Pclk);
+        rcc.cfgr3.modify(|_, w| {
+            #[cfg(any(
+                feature = "stm32f302",
+                feature = "stm32f303",
+                feature = "stm32f373",
+                feature = "stm32f378",
+                feature = "stm32f358",
+                feature = "stm32f398"
+            ))]
+            w.usart2sw()
+                .bits(usart2_source.bits())
+                .usart3sw()
+                .bits(usart3_source.bits());
+            #[cfg(any(
+                feature = "stm32f302xb",
+                feature = "stm32f302xc",
+                feature = "stm32f302xd",
+                feature = "stm32f302xe",
+                feature = "stm32f303xb",
+                feature = "stm32f303xc",
+                feature = "stm32f303xd",
+                feature = "stm32f303xe",
+                feature = "stm32f358",
+                feature = "stm32f398"
+            ))]
+            w.uart4sw()
+                .bits(self.uart4_source.unwrap_or(UsartClockSource::Pclk).bits())
+                .uart5sw()
+                .bits(self.uart5_source.unwrap_or(UsartClockSource::Pclk).bits());
+            w.usart1sw().bits(usart1_source.bits())
+        });
+
         Clocks {
             hclk: Hertz(hclk),
             pclk1: Hertz(pclk1),
@@ -751,6 +939,43 @@ impl CFGR {
             ppre1,
             ppre2,
             sysclk: Hertz(sysclk),
+            usart1clk: Hertz(usart1_source.frequency(pclk2, sysclk)),
+            usart2clk: Hertz(usart2_source.frequency(pclk1, sysclk)),
+            usart3clk: Hertz(usart3_source.frequency(pclk1, sysclk)),
+            #[cfg(any(
+                feature = "stm32f302xb",
+                feature = "stm32f302xc",
+                feature = "stm32f302xd",
+                feature = "stm32f302xe",
+                feature = "stm32f303xb",
+                feature = "stm32f303xc",
+                feature = "stm32f303xd",
+                feature = "stm32f303xe",
+                feature = "stm32f358",
+                feature = "stm32f398"
+            ))]
+            uart4clk: Hertz(
+                self.uart4_source
+                    .unwrap_or(UsartClockSource::Pclk)
+                    .frequency(pclk1, sysclk),
+            ),
+            #[cfg(any(
+                feature = "stm32f302xb",
+                feature = "stm32f302xc",
+                feature = "stm32f302xd",
+                feature = "stm32f302xe",
+                feature = "stm32f303xb",
+                feature = "stm32f303xc",
+                feature = "stm32f303xd",
+                feature = "stm32f303xe",
+                feature = "stm32f358",
+                feature = "stm32f398"
+            ))]
+            uart5clk: Hertz(
+                self.uart5_source
+                    .unwrap_or(UsartClockSource::Pclk)
+                    .frequency(pclk1, sysclk),
+            ),
             usbclk_valid,
         }
     }
@@ -768,6 +993,35 @@ pub struct Clocks {
     ppre1: u8,
     ppre2: u8,
     sysclk: Hertz,
+    usart1clk: Hertz,
+    usart2clk: Hertz,
+    usart3clk: Hertz,
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    uart4clk: Hertz,
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    uart5clk: Hertz,
     usbclk_valid: bool,
 }
 
@@ -787,14 +1041,18 @@ impl Clocks {
         self.pclk2
     }
 
-    pub(crate) fn ppre1(&self) -> u8 {
-        self.ppre1
+    /// Returns the frequency of the timers connected to APB1
+    ///
+    /// The timer clock is twice the APB1 frequency, if APB1 is prescaled.
+    pub(crate) fn timclk1(&self) -> Hertz {
+        Hertz(self.pclk1.0 * if self.ppre1 == 1 { 1 } else { 2 })
     }
 
-    // TODO remove `allow`
-    #[allow(dead_code)]
-    pub(crate) fn ppre2(&self) -> u8 {
-        self.ppre2
+    /// Returns the frequency of the timers connected to APB2
+    ///
+    /// The timer clock is twice the APB2 frequency, if APB2 is prescaled.
+    pub(crate) fn timclk2(&self) -> Hertz {
+        Hertz(self.pclk2.0 * if self.ppre2 == 1 { 1 } else { 2 })
     }
 
     /// Returns the system (core) frequency
@@ -802,6 +1060,55 @@ impl Clocks {
         self.sysclk
     }
 
+    /// Returns the frequency of the USART1 clock
+    pub fn usart1clk(&self) -> Hertz {
+        self.usart1clk
+    }
+
+    /// Returns the frequency of the USART2 clock
+    pub fn usart2clk(&self) -> Hertz {
+        self.usart2clk
+    }
+
+    /// Returns the frequency of the USART3 clock
+    pub fn usart3clk(&self) -> Hertz {
+        self.usart3clk
+    }
+
+    /// Returns the frequency of the UART4 clock
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    pub fn uart4clk(&self) -> Hertz {
+        self.uart4clk
+    }
+
+    /// Returns the frequency of the UART5 clock
+    #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    pub fn uart5clk(&self) -> Hertz {
+        self.uart5clk
+    }
+
     /// Returns whether the USBCLK clock frequency is valid for the USB peripheral
     ///
     /// If the microcontroller does support USB, 48 Mhz or 72 Mhz have to be used
@@ -3,12 +3,18 @@
 use crate::{
     gpio::{gpioa, gpiob, gpioc, AF7},
     hal::{blocking, serial},
-    pac::{USART1, USART2, USART3},
+    pac::{usart1, USART1, USART2, USART3},
     rcc::{Clocks, APB1, APB2},
-    time::Bps,
+    time::{Bps, Hertz, U32Ext},
 };
 use cfg_if::cfg_if;
-use core::{convert::Infallible, marker::PhantomData, ptr};
+use core::{
+    convert::Infallible,
+    fmt,
+    marker::PhantomData,
+    ptr, slice,
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
 
 cfg_if! {
     if #[cfg(any(feature = "stm32f302", feature = "stm32f303"))] {
@@ -18,15 +24,58 @@ cfg_if! {
 }
 
 /// Interrupt event
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Event {
     /// New data has been received
     Rxne,
     /// New data can be sent
     Txe,
+    /// The transmission of the last data has been completed
+    TransmissionComplete,
+    /// The receive line is idle for a whole frame after a reception
+    Idle,
+    /// The character set with [`Serial::set_match_character`] has been received
+    CharacterMatch,
+    /// The receive line is idle for the duration set with
+    /// [`Serial::set_receiver_timeout`] after the last reception
+    ReceiverTimeout,
+    /// The wake-up source set with [`Serial::enable_wakeup_from_stop`]
+    /// has been detected
+    WakeupFromStop,
+}
+
+/// Returns `true`, if the flag of the event is set
+fn is_event_triggered(usart: &usart1::RegisterBlock, event: Event) -> bool {
+    let isr = usart.isr.read();
+    match event {
+        Event::Rxne => isr.rxne().bit_is_set(),
+        Event::Txe => isr.txe().bit_is_set(),
+        Event::TransmissionComplete => isr.tc().bit_is_set(),
+        Event::Idle => isr.idle().bit_is_set(),
+        Event::CharacterMatch => isr.cmf().bit_is_set(),
+        Event::ReceiverTimeout => isr.rtof().bit_is_set(),
+        Event::WakeupFromStop => isr.wuf().bit_is_set(),
+    }
+}
+
+/// Clears the flag of the event
+fn clear_event(usart: &usart1::RegisterBlock, event: Event) {
+    // NOTE(write): writing 0 to the other bits has no effect
+    match event {
+        Event::Rxne => usart.rqr.write(|w| w.rxfrq().discard()),
+        // The flag is only cleared by writing new data
+        Event::Txe => (),
+        Event::TransmissionComplete => usart.icr.write(|w| w.tccf().clear()),
+        Event::Idle => usart.icr.write(|w| w.idlecf().clear()),
+        Event::CharacterMatch => usart.icr.write(|w| w.cmcf().clear()),
+        Event::ReceiverTimeout => usart.icr.write(|w| w.rtocf().clear()),
+        Event::WakeupFromStop => usart.icr.write(|w| w.wucf().clear()),
+    }
 }
 
 /// Serial error
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 #[non_exhaustive]
 pub enum Error {
     /// Framing error
@@ -39,13 +88,327 @@ pub enum Error {
     Parity,
 }
 
+impl Error {
+    /// Bit of the error flag in the USART_ISR register
+    fn mask(self) -> u8 {
+        match self {
+            Error::Parity => 1 << 0,
+            Error::Framing => 1 << 1,
+            Error::Noise => 1 << 2,
+            Error::Overrun => 1 << 3,
+        }
+    }
+}
+
+/// Set of receive errors, which have occurred
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Errors(u8);
+
+impl Errors {
+    /// Reads the error flags of the USART
+    fn read(usart: &usart1::RegisterBlock) -> Self {
+        Errors((usart.isr.read().bits() & 0b1111) as u8)
+    }
+
+    /// Returns `true`, if no error has occurred
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true`, if the error has occurred
+    pub fn contains(self, error: Error) -> bool {
+        self.0 & error.mask() != 0
+    }
+}
+
+/// Clears all error flags of the USART
+fn clear_errors(usart: &usart1::RegisterBlock) {
+    // NOTE(write): writing 0 to the other bits has no effect
+    usart.icr.write(|w| {
+        w.pecf().clear();
+        w.fecf().clear();
+        w.ncf().clear();
+        w.orecf().clear()
+    });
+}
+
+/// Error of the serial configuration
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The baud rate is too high for the USART clock,
+    /// even with the selected oversampling
+    BaudRateTooHigh,
+    /// The baud rate is too low for the USART clock
+    BaudRateTooLow,
+}
+
+/// The auto baud rate detection has failed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoBaudRateError;
+
+/// Mode of the auto baud rate detection, which selects the
+/// pattern of the first received character
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoBaudRateMode {
+    /// Measures the duration of the start bit,
+    /// the character has to start with a `1` bit
+    StartBit,
+    /// Measures the duration between the falling edges of the start bit and
+    /// the first data bit, the character has to start with `10` (LSB first)
+    FallingEdge,
+    /// The character has to be `0x7F`
+    Frame0x7F,
+    /// The character has to be `0x55`
+    Frame0x55,
+}
+
+/// Activity on the receive line, which wakes the MCU up from Stop mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WakeupSource {
+    /// The character set with [`Serial::set_match_character`] has been received
+    AddressMatch,
+    /// A start bit has been detected
+    StartBit,
+    /// A whole character has been received
+    Rxne,
+}
+
+/// Parity check
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parity {
+    /// No parity bit
+    None,
+    /// Even parity
+    Even,
+    /// Odd parity
+    Odd,
+}
+
+/// Number of stop bits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopBits {
+    /// 0.5 stop bits
+    Stop0p5,
+    /// 1 stop bit
+    Stop1,
+    /// 1.5 stop bits
+    Stop1p5,
+    /// 2 stop bits
+    Stop2,
+}
+
+/// Word length
+///
+/// The word length includes the parity bit, if parity is enabled.
+/// The parity bit is the most significant bit of the received word.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WordLength {
+    /// 7 bit words
+    Bits7,
+    /// 8 bit words
+    Bits8,
+    /// 9 bit words, which have to be read and written as `u16`
+    Bits9,
+}
+
+/// Oversampling of the receiver
+///
+/// Oversampling by 8 doubles the maximum baud rate,
+/// but makes the receiver less tolerant to clock deviations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Oversampling {
+    /// Oversampling by 16
+    By16,
+    /// Oversampling by 8
+    By8,
+}
+
+/// Polarity of the RS-485 driver enable signal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DePolarity {
+    /// The driver is enabled with a high level
+    ActiveHigh,
+    /// The driver is enabled with a low level
+    ActiveLow,
+}
+
+/// Configuration of the RS-485 driver enable signal
+///
+/// The times are given in sample time units, which are 1/16 or 1/8
+/// of a bit duration, depending on the [`Oversampling`].
+/// The default configuration is active high without any delays.
+#[derive(Clone, Copy, Debug)]
+pub struct DeConfig {
+    /// Polarity of the driver enable signal
+    pub polarity: DePolarity,
+    /// Time between the activation of the driver enable signal
+    /// and the beginning of the start bit (`0..=31`)
+    pub assertion_time: u8,
+    /// Time between the end of the last stop bit and the
+    /// deactivation of the driver enable signal (`0..=31`)
+    pub deassertion_time: u8,
+}
+
+impl Default for DeConfig {
+    fn default() -> Self {
+        DeConfig {
+            polarity: DePolarity::ActiveHigh,
+            assertion_time: 0,
+            deassertion_time: 0,
+        }
+    }
+}
+
+/// Serial configuration
+///
+/// The default configuration is 115200 baud, 8 data bits, no parity,
+/// 1 stop bit (8N1) and oversampling by 16.
+/// A baud rate converts into the default configuration with this baud rate.
+///
+/// ```
+/// let config = Config::default()
+///     .baud_rate(9600.bps())
+///     .parity(Parity::Even)
+///     .word_length(WordLength::Bits9);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Baud rate
+    pub baud_rate: Bps,
+    /// Parity check
+    pub parity: Parity,
+    /// Number of stop bits
+    pub stop_bits: StopBits,
+    /// Word length
+    pub word_length: WordLength,
+    /// Oversampling of the receiver
+    pub oversampling: Oversampling,
+}
+
+impl Config {
+    /// Sets the baud rate
+    pub fn baud_rate(mut self, baud_rate: Bps) -> Self {
+        self.baud_rate = baud_rate;
+        self
+    }
+
+    /// Sets the parity check
+    pub fn parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    /// Sets the number of stop bits
+    pub fn stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+
+    /// Sets the word length
+    pub fn word_length(mut self, word_length: WordLength) -> Self {
+        self.word_length = word_length;
+        self
+    }
+
+    /// Sets the oversampling of the receiver
+    pub fn oversampling(mut self, oversampling: Oversampling) -> Self {
+        self.oversampling = oversampling;
+        self
+    }
+
+    /// Value of the USART_BRR register for the given USART clock
+    ///
+    /// The USART clock divider is rounded to the nearest value.
+    fn brr(&self, clock: Hertz) -> Result<u32, ConfigError> {
+        let (clock, baud_rate) = (u64::from(clock.0), u64::from(self.baud_rate.0));
+        let samples = match self.oversampling {
+            Oversampling::By16 => 1,
+            Oversampling::By8 => 2,
+        };
+        let usartdiv = (samples * clock + baud_rate / 2)
+            .checked_div(baud_rate)
+            .ok_or(ConfigError::BaudRateTooLow)?;
+        match usartdiv {
+            0..=15 => Err(ConfigError::BaudRateTooHigh),
+            16..=0xffff => Ok(match self.oversampling {
+                Oversampling::By16 => usartdiv as u32,
+                // BRR[3] has to be kept cleared and BRR[2:0] is USARTDIV[3:0] shifted right
+                Oversampling::By8 => ((usartdiv & !0xf) | ((usartdiv & 0xf) >> 1)) as u32,
+            }),
+            _ => Err(ConfigError::BaudRateTooLow),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            baud_rate: 115_200.bps(),
+            parity: Parity::None,
+            stop_bits: StopBits::Stop1,
+            word_length: WordLength::Bits8,
+            oversampling: Oversampling::By16,
+        }
+    }
+}
+
+impl From<Bps> for Config {
+    fn from(baud_rate: Bps) -> Self {
+        Config::default().baud_rate(baud_rate)
+    }
+}
+
 // FIXME these should be "closed" traits
 /// TX pin - DO NOT IMPLEMENT THIS TRAIT
+///
+/// # Safety
+///
+/// The pin has to be connected to the TX output of the USART in its current
+/// alternate function mode, as the transmitter is enabled without checking
+/// the pin.
 pub unsafe trait TxPin<USART> {}
 
 /// RX pin - DO NOT IMPLEMENT THIS TRAIT
+///
+/// # Safety
+///
+/// The pin has to be connected to the RX input of the USART in its current
+/// alternate function mode, as the receiver is enabled without checking
+/// the pin.
 pub unsafe trait RxPin<USART> {}
 
+/// CTS pin - DO NOT IMPLEMENT THIS TRAIT
+///
+/// # Safety
+///
+/// The pin has to be connected to the CTS input of the USART in its current
+/// alternate function mode, as the hardware flow control is enabled without
+/// checking the pin.
+pub unsafe trait CtsPin<USART> {}
+
+/// RTS pin - DO NOT IMPLEMENT THIS TRAIT
+///
+/// # Safety
+///
+/// The pin has to be connected to the RTS output of the USART in its current
+/// alternate function mode, as the hardware flow control is enabled without
+/// checking the pin.
+pub unsafe trait RtsPin<USART> {}
+
+/// RS-485 driver enable pin - DO NOT IMPLEMENT THIS TRAIT
+///
+/// The driver enable signal is output on the RTS pin.
+///
+/// # Safety
+///
+/// The pin has to be connected to the RTS (DE) output of the USART in its
+/// current alternate function mode, as the driver enable output is enabled
+/// without checking the pin.
+pub unsafe trait DePin<USART> {}
+
+unsafe impl<USART, PIN: RtsPin<USART>> DePin<USART> for PIN {}
+
 unsafe impl TxPin<USART1> for gpioa::PA9<AF7> {}
 unsafe impl TxPin<USART1> for gpiob::PB6<AF7> {}
 unsafe impl TxPin<USART1> for gpioc::PC4<AF7> {}
@@ -62,9 +425,18 @@ unsafe impl TxPin<USART3> for gpiob::PB10<AF7> {}
 unsafe impl TxPin<USART3> for gpioc::PC10<AF7> {}
 unsafe impl RxPin<USART3> for gpioc::PC11<AF7> {}
 
+unsafe impl CtsPin<USART1> for gpioa::PA11<AF7> {}
+unsafe impl RtsPin<USART1> for gpioa::PA12<AF7> {}
+
+unsafe impl CtsPin<USART2> for gpioa::PA0<AF7> {}
+unsafe impl RtsPin<USART2> for gpioa::PA1<AF7> {}
+
+unsafe impl CtsPin<USART3> for gpioa::PA13<AF7> {}
+unsafe impl RtsPin<USART3> for gpiob::PB14<AF7> {}
+
 cfg_if! {
     if #[cfg(any(feature = "gpio-f303", feature = "gpio-f303e", feature = "gpio-f373"))] {
-        use crate::gpio::{gpiod, gpioe};
+        use crate::gpio::{gpiod, gpioe, gpiof};
 
         unsafe impl TxPin<USART1> for gpioe::PE0<AF7> {}
         unsafe impl RxPin<USART1> for gpioe::PE1<AF7> {}
@@ -75,6 +447,36 @@ cfg_if! {
         unsafe impl TxPin<USART3> for gpiod::PD8<AF7> {}
         unsafe impl RxPin<USART3> for gpiod::PD9<AF7> {}
         unsafe impl RxPin<USART3> for gpioe::PE15<AF7> {}
+
+        unsafe impl CtsPin<USART2> for gpiod::PD3<AF7> {}
+        unsafe impl RtsPin<USART2> for gpiod::PD4<AF7> {}
+
+        unsafe impl CtsPin<USART3> for gpiod::PD11<AF7> {}
+        unsafe impl RtsPin<USART3> for gpiod::PD12<AF7> {}
+        unsafe impl RtsPin<USART3> for gpiof::PF6<AF7> {}
+    }
+}
+cfg_if! {
+    if #[cfg(any(
+        feature = "stm32f302xb",
+        feature = "stm32f302xc",
+        feature = "stm32f302xd",
+        feature = "stm32f302xe",
+        feature = "stm32f303xb",
+        feature = "stm32f303xc",
+        feature = "stm32f303xd",
+        feature = "stm32f303xe",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))] {
+        use crate::gpio::AF5;
+        use crate::pac::{UART4, UART5};
+
+        unsafe impl TxPin<UART4> for gpioc::PC10<AF5> {}
+        unsafe impl RxPin<UART4> for gpioc::PC11<AF5> {}
+
+        unsafe impl TxPin<UART5> for gpioc::PC12<AF5> {}
+        unsafe impl RxPin<UART5> for gpiod::PD2<AF5> {}
     }
 }
 cfg_if! {
@@ -82,6 +484,7 @@ cfg_if! {
         unsafe impl TxPin<USART2> for gpioa::PA14<AF7> {}
         unsafe impl RxPin<USART2> for gpioa::PA15<AF7> {}
         unsafe impl RxPin<USART3> for gpiob::PB11<AF7> {}
+        unsafe impl CtsPin<USART3> for gpiob::PB13<AF7> {}
     }
 }
 
@@ -101,64 +504,558 @@ pub struct Tx<USART> {
     _usart: PhantomData<USART>,
 }
 
+/// Lock-free single-producer single-consumer ring buffer
+/// over a caller provided static buffer
+///
+/// `head` is only written by the producer and `tail` only by the consumer.
+/// The bytes from `tail` up to `head` belong to the consumer, the others to
+/// the producer, so one byte of the buffer always stays unused.
+struct RingBuffer {
+    buffer: *mut u8,
+    len: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// NOTE(unsafe) the producer and the consumer never access the same byte,
+// and the bytes are handed over by the release stores of `head` and `tail`
+unsafe impl Send for RingBuffer {}
+unsafe impl Sync for RingBuffer {}
+
+impl RingBuffer {
+    fn new(buffer: &'static mut [u8]) -> Self {
+        RingBuffer {
+            buffer: buffer.as_mut_ptr(),
+            len: buffer.len(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Index after `index`
+    fn next(&self, index: usize) -> usize {
+        if index + 1 == self.len {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+    }
+
+    /// Appends a byte, returns `false` if the buffer is full
+    ///
+    /// Must only be called by the producer.
+    fn push(&self, byte: u8) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let next = self.next(head);
+        if next == self.tail.load(Ordering::Acquire) {
+            return false;
+        }
+        // NOTE(unsafe) `head` is in bounds and belongs to the producer
+        unsafe { self.buffer.add(head).write(byte) };
+        self.head.store(next, Ordering::Release);
+        true
+    }
+
+    /// Removes the oldest byte
+    ///
+    /// Must only be called by the consumer.
+    fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail == self.head.load(Ordering::Acquire) {
+            return None;
+        }
+        // NOTE(unsafe) `tail` is in bounds and belongs to the consumer
+        let byte = unsafe { self.buffer.add(tail).read() };
+        self.tail.store(self.next(tail), Ordering::Release);
+        Some(byte)
+    }
+
+    /// Releases the buffer
+    fn release(self) -> &'static mut [u8] {
+        // NOTE(unsafe) the buffer has been borrowed by `new`
+        unsafe { slice::from_raw_parts_mut(self.buffer, self.len) }
+    }
+}
+
+/// Buffers and receive errors shared by the halves of a [`BufferedSerial`]
+struct Buffers {
+    rx: RingBuffer,
+    tx: RingBuffer,
+    errors: AtomicU8,
+}
+
+/// Length of the LIN break, which is detected by the receiver
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BreakLength {
+    /// 10 bit break detection
+    Bits10,
+    /// 11 bit break detection
+    Bits11,
+}
+
+/// Checksum model of a LIN frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Checksum {
+    /// Classic checksum over the data bytes (LIN 1.x and diagnostic frames)
+    Classic,
+    /// Enhanced checksum over the protected identifier and the data bytes (LIN 2.x)
+    Enhanced,
+}
+
+/// LIN error
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum LinError {
+    /// Reception error of the USART
+    Serial(Error),
+    /// The received sync field is not `0x55`
+    Sync,
+    /// The parity bits of the received protected identifier are wrong
+    IdParity,
+    /// The received checksum does not match the data
+    Checksum,
+    /// No byte has been received within the receiver timeout
+    ///
+    /// See [`Serial::set_receiver_timeout`].
+    Timeout,
+}
+
+impl From<Error> for LinError {
+    fn from(error: Error) -> Self {
+        LinError::Serial(error)
+    }
+}
+
+/// Maximum response time of a LIN frame with 8 data bytes in bit durations,
+/// which is 140 % of the nominal transmission time of the data and the checksum
+const LIN_RESPONSE_TIMEOUT: u32 = 126;
+
+/// Returns the protected identifier of a LIN frame, which is the
+/// identifier with the two parity bits
+///
+/// # Panics
+///
+/// Panics, if the identifier is greater than `63`.
+pub fn protected_id(id: u8) -> u8 {
+    crate::assert!(id < 64);
+    let bit = |n: u8| (id >> n) & 1;
+    let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+    let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+    id | (p0 << 6) | (p1 << 7)
+}
+
+/// Returns the checksum of a LIN frame with the given protected identifier and data
+pub fn checksum(protected_id: u8, data: &[u8], model: Checksum) -> u8 {
+    let initial = match model {
+        Checksum::Classic => 0,
+        Checksum::Enhanced => u16::from(protected_id),
+    };
+    // Sum with carry, where every carry is added to the sum again
+    let sum = data.iter().fold(initial, |sum, &byte| {
+        let sum = sum + u16::from(byte);
+        if sum > 0xff {
+            sum - 0xff
+        } else {
+            sum
+        }
+    });
+    !(sum as u8)
+}
+
+/// LIN master or slave node
+///
+/// The USART sends and detects the LIN break, while the rest of the frame is
+/// transferred as normal 8 bit data without parity. The node receives its own
+/// transmissions as echo over the LIN bus, which is discarded.
+///
+/// The transfers block until they are completed. The transmissions take at
+/// most the transmission time of the frame, as the transmitter can not be
+/// stalled. The receptions wait at most the receiver timeout for each byte,
+/// which is set to the maximum response time of a frame with 8 data bytes
+/// (126 bit durations) by [`Serial::into_lin`], unless another receiver
+/// timeout has been set with [`Serial::set_receiver_timeout`] before.
+/// A missing byte is reported as [`LinError::Timeout`].
+///
+/// ```
+/// let mut lin = serial.into_lin(BreakLength::Bits11);
+///
+/// // Master requesting 2 bytes from the slave with identifier 0x10
+/// lin.send_header(0x10);
+/// let mut response = [0; 2];
+/// lin.read_response(0x10, &mut response, Checksum::Enhanced)?;
+/// ```
+pub struct Lin<USART, PINS> {
+    serial: Serial<USART, PINS>,
+}
+
+/// Interrupt driven serial communication with ring buffers
+///
+/// The received bytes and the bytes to transmit are stored in caller provided
+/// static buffers. The `BufferedSerial` is [`split`](BufferedSerial::split)
+/// into a [`BufferedSerialIsr`], whose
+/// [`on_interrupt`](BufferedSerialIsr::on_interrupt) has to be called from
+/// the interrupt handler of the USART, and a [`BufferedSerialApp`], which
+/// reads and writes the buffers and never blocks.
+///
+/// The halves share lock-free ring buffers, so the application half does not
+/// need a critical section and the interrupt handler is never delayed by it.
+///
+/// ```
+/// static mut RX_BUFFER: [u8; 64] = [0; 64];
+/// static mut TX_BUFFER: [u8; 64] = [0; 64];
+/// static mut SERIAL_ISR: Option<BufferedSerialIsr<'static, USART1>> = None;
+///
+/// let serial = Serial::usart1(dp.USART1, (tx, rx), 115_200.bps(), clocks, &mut rcc.apb2);
+/// let serial = cortex_m::singleton!(: BufferedSerial<USART1, (PA9<AF7>, PA10<AF7>)> =
+///     serial.into_buffered(unsafe { &mut RX_BUFFER }, unsafe { &mut TX_BUFFER })).unwrap();
+/// let (isr, mut app) = serial.split();
+/// unsafe { SERIAL_ISR = Some(isr) };
+/// unsafe { NVIC::unmask(Interrupt::USART1_EXTI25) };
+///
+/// writeln!(app, "Hello").ok();
+///
+/// #[interrupt]
+/// fn USART1_EXTI25() {
+///     unsafe { SERIAL_ISR.as_mut() }.unwrap().on_interrupt();
+/// }
+/// ```
+pub struct BufferedSerial<USART, PINS> {
+    serial: Serial<USART, PINS>,
+    buffers: Buffers,
+}
+
+/// Interrupt half of a [`BufferedSerial`], which moves the bytes
+/// between the USART and the buffers
+pub struct BufferedSerialIsr<'a, USART> {
+    buffers: &'a Buffers,
+    _usart: PhantomData<USART>,
+}
+
+/// Application half of a [`BufferedSerial`], which reads the received bytes
+/// and queues the bytes to transmit
+pub struct BufferedSerialApp<'a, USART> {
+    buffers: &'a Buffers,
+    _usart: PhantomData<USART>,
+}
+
 macro_rules! hal {
     ($(
-        $USARTX:ident: ($usartX:ident, $APB:ident, $usartXen:ident, $usartXrst:ident, $pclkX:ident),
+        $USARTX:ident: ($usartX:ident, $APB:ident, $usartXen:ident, $usartXrst:ident, $usartXclk:ident),
     )+) => {
         $(
             impl<TX, RX> Serial<$USARTX, (TX, RX)> {
                 /// Configures a USART peripheral to provide serial communication
-                pub fn $usartX(
+                ///
+                /// The configuration is either a [`Config`] or just a baud rate,
+                /// which selects the default 8N1 configuration.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the baud rate can not be reached with the USART clock.
+                /// Use [`configure`](Self::configure) to handle this error.
+                pub fn $usartX<C>(
                     usart: $USARTX,
                     pins: (TX, RX),
-                    baud_rate: Bps,
+                    config: C,
                     clocks: Clocks,
                     apb: &mut $APB,
                 ) -> Self
                 where
                     TX: TxPin<$USARTX>,
                     RX: RxPin<$USARTX>,
+                    C: Into<Config>,
                 {
+                    let mut serial = Self::reset(usart, pins, apb);
+                    crate::expect!(serial.configure(config, clocks).ok(), "impossible baud rate");
+                    serial
+                }
+            }
+
+            paste::paste! {
+                impl<TX, RX, CTS, RTS> Serial<$USARTX, (TX, RX, CTS, RTS)> {
+                    /// Configures a USART peripheral to provide serial communication
+                    /// with hardware flow control
+                    ///
+                    /// The transmitter only sends data, while the CTS input is low,
+                    /// and the RTS output is low, while the receiver is ready to receive data.
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics, if the baud rate can not be reached with the USART clock.
+                    pub fn [<$usartX _with_flow_control>]<C>(
+                        usart: $USARTX,
+                        pins: (TX, RX, CTS, RTS),
+                        config: C,
+                        clocks: Clocks,
+                        apb: &mut $APB,
+                    ) -> Self
+                    where
+                        TX: TxPin<$USARTX>,
+                        RX: RxPin<$USARTX>,
+                        CTS: CtsPin<$USARTX>,
+                        RTS: RtsPin<$USARTX>,
+                        C: Into<Config>,
+                    {
+                        let mut serial = Self::reset(usart, pins, apb);
+
+                        // The flow control can only be enabled while the USART is disabled
+                        serial.usart.cr3.modify(|_, w| {
+                            w.ctse().enabled();
+                            w.rtse().enabled()
+                        });
+
+                        crate::expect!(serial.configure(config, clocks).ok(), "impossible baud rate");
+                        serial
+                    }
+                }
+
+                impl<TX, RX, DE> Serial<$USARTX, (TX, RX, DE)> {
+                    /// Configures a USART peripheral to provide serial communication
+                    /// over an RS-485 transceiver, whose driver is enabled by the
+                    /// hardware with the `DE` pin during transmission
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics, if the baud rate can not be reached with the USART clock
+                    /// or if a time of the driver enable configuration is greater than `31`.
+                    pub fn [<$usartX _rs485>]<C>(
+                        usart: $USARTX,
+                        pins: (TX, RX, DE),
+                        config: C,
+                        de_config: DeConfig,
+                        clocks: Clocks,
+                        apb: &mut $APB,
+                    ) -> Self
+                    where
+                        TX: TxPin<$USARTX>,
+                        RX: RxPin<$USARTX>,
+                        DE: DePin<$USARTX>,
+                        C: Into<Config>,
+                    {
+                        crate::assert!(de_config.assertion_time <= 0b1_1111);
+                        crate::assert!(de_config.deassertion_time <= 0b1_1111);
+
+                        let mut serial = Self::reset(usart, pins, apb);
+
+                        // The driver enable mode can only be configured while the USART is disabled
+                        serial.usart.cr1.modify(|_, w| {
+                            w.deat().bits(de_config.assertion_time);
+                            w.dedt().bits(de_config.deassertion_time)
+                        });
+                        serial.usart.cr3.modify(|_, w| {
+                            w.dep().bit(de_config.polarity == DePolarity::ActiveLow);
+                            w.dem().enabled()
+                        });
+
+                        crate::expect!(serial.configure(config, clocks).ok(), "impossible baud rate");
+                        serial
+                    }
+                }
+
+                impl<TX> Serial<$USARTX, TX> {
+                    /// Configures a USART peripheral to provide half-duplex serial
+                    /// communication over the single TX pin
+                    ///
+                    /// The TX pin is used for transmission and reception, so it should be
+                    /// configured as open drain output with a pull up, e.g. with
+                    /// `into_af7_open_drain`. The transmitter releases the line, while it is idle.
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics, if the baud rate can not be reached with the USART clock.
+                    pub fn [<$usartX _half_duplex>]<C>(
+                        usart: $USARTX,
+                        pin: TX,
+                        config: C,
+                        clocks: Clocks,
+                        apb: &mut $APB,
+                    ) -> Self
+                    where
+                        TX: TxPin<$USARTX>,
+                        C: Into<Config>,
+                    {
+                        let mut serial = Self::reset(usart, pin, apb);
+
+                        // The half-duplex mode can only be selected while the USART is disabled
+                        serial.usart.cr3.modify(|_, w| w.hdsel().selected());
+
+                        crate::expect!(serial.configure(config, clocks).ok(), "impossible baud rate");
+                        serial
+                    }
+                }
+            }
+
+            impl<PINS> Serial<$USARTX, PINS> {
+                /// Enables the USART peripheral and resets it to a clean slate state
+                fn reset(usart: $USARTX, pins: PINS, apb: &mut $APB) -> Self {
                     // enable or reset $USARTX
                     apb.enr().modify(|_, w| w.$usartXen().set_bit());
                     apb.rstr().modify(|_, w| w.$usartXrst().set_bit());
                     apb.rstr().modify(|_, w| w.$usartXrst().clear_bit());
 
-                    let brr = clocks.$pclkX().0 / baud_rate.0;
-                    crate::assert!(brr >= 16, "impossible baud rate");
+                    Serial { usart, pins }
+                }
+
+                /// Changes the configuration of the serial communication
+                ///
+                /// The USART is disabled during the reconfiguration, so an ongoing
+                /// transmission or reception is aborted.
+                /// The configuration is left unchanged, if the baud rate can not
+                /// be reached with the USART clock.
+                pub fn configure<C: Into<Config>>(&mut self, config: C, clocks: Clocks) -> Result<(), ConfigError> {
+                    let config = config.into();
+                    let brr = config.brr(clocks.$usartXclk())?;
+
+                    // The word length, parity, stop bits and oversampling
+                    // can only be changed while the USART is disabled
+                    self.usart.cr1.modify(|_, w| w.ue().disabled());
+
                     // NOTE(write): uses all bits of this register.
-                    usart.brr.write(|w| unsafe { w.bits(brr) });
+                    self.usart.brr.write(|w| unsafe { w.bits(brr) });
 
-                    usart.cr1.modify(|_, w| {
+                    self.usart.cr2.modify(|_, w| match config.stop_bits {
+                        StopBits::Stop0p5 => w.stop().stop0p5(),
+                        StopBits::Stop1 => w.stop().stop1(),
+                        StopBits::Stop1p5 => w.stop().stop1p5(),
+                        StopBits::Stop2 => w.stop().stop2(),
+                    });
+
+                    // NOTE(unsafe): M1 (bit 28) is not modeled by the PAC
+                    self.usart.cr1.modify(|r, w| unsafe {
+                        let m1 = match config.word_length {
+                            WordLength::Bits7 => 1 << 28,
+                            WordLength::Bits8 | WordLength::Bits9 => 0,
+                        };
+                        w.bits((r.bits() & !(1 << 28)) | m1);
+                        w.m().bit(config.word_length == WordLength::Bits9);
+                        w.pce().bit(config.parity != Parity::None);
+                        w.ps().bit(config.parity == Parity::Odd);
+                        w.over8().bit(config.oversampling == Oversampling::By8);
                         w.ue().enabled();  // enable USART
                         w.re().enabled();  // enable receiver
                         w.te().enabled()   // enable transmitter
                     });
 
-                    Serial { usart, pins }
+                    Ok(())
                 }
 
                 /// Starts listening for an interrupt event
                 pub fn listen(&mut self, event: Event) {
                     match event {
-                        Event::Rxne => {
-                            self.usart.cr1.modify(|_, w| w.rxneie().set_bit())
-                        },
-                        Event::Txe => {
-                            self.usart.cr1.modify(|_, w| w.txeie().set_bit())
-                        },
+                        Event::WakeupFromStop => self.usart.cr3.modify(|_, w| w.wufie().set_bit()),
+                        _ => self.usart.cr1.modify(|_, w| match event {
+                            Event::Rxne => w.rxneie().set_bit(),
+                            Event::Txe => w.txeie().set_bit(),
+                            Event::TransmissionComplete => w.tcie().set_bit(),
+                            Event::Idle => w.idleie().set_bit(),
+                            Event::CharacterMatch => w.cmie().set_bit(),
+                            Event::ReceiverTimeout => w.rtoie().set_bit(),
+                            Event::WakeupFromStop => w,
+                        }),
                     }
                 }
 
-                /// Starts listening for an interrupt event
+                /// Stops listening for an interrupt event
                 pub fn unlisten(&mut self, event: Event) {
                     match event {
-                        Event::Rxne => {
-                            self.usart.cr1.modify(|_, w| w.rxneie().clear_bit())
-                        },
-                        Event::Txe => {
-                            self.usart.cr1.modify(|_, w| w.txeie().clear_bit())
-                        },
+                        Event::WakeupFromStop => self.usart.cr3.modify(|_, w| w.wufie().clear_bit()),
+                        _ => self.usart.cr1.modify(|_, w| match event {
+                            Event::Rxne => w.rxneie().clear_bit(),
+                            Event::Txe => w.txeie().clear_bit(),
+                            Event::TransmissionComplete => w.tcie().clear_bit(),
+                            Event::Idle => w.idleie().clear_bit(),
+                            Event::CharacterMatch => w.cmie().clear_bit(),
+                            Event::ReceiverTimeout => w.rtoie().clear_bit(),
+                            Event::WakeupFromStop => w,
+                        }),
+                    }
+                }
+
+                /// Returns `true`, if the flag of the event is set
+                ///
+                /// The flags are set independently of listening for the event.
+                pub fn is_event_triggered(&self, event: Event) -> bool {
+                    is_event_triggered(&self.usart, event)
+                }
+
+                /// Clears the flag of the event
+                ///
+                /// Clearing [`Event::Rxne`] discards the received data.
+                /// [`Event::Txe`] can only be cleared by writing new data.
+                pub fn clear_event(&mut self, event: Event) {
+                    clear_event(&self.usart, event)
+                }
+
+                /// Returns the receive errors, which have occurred since
+                /// the errors were cleared the last time
+                pub fn errors(&self) -> Errors {
+                    Errors::read(&self.usart)
+                }
+
+                /// Returns the current baud rate, calculated from the USART clock divider
+                ///
+                /// After an auto baud rate detection, this is the detected baud rate.
+                pub fn baud_rate(&self, clocks: Clocks) -> Bps {
+                    let brr = self.usart.brr.read().bits();
+                    let (samples, usartdiv) = if self.usart.cr1.read().over8().bit_is_set() {
+                        (2, (brr & !0xf) | ((brr & 0x7) << 1))
+                    } else {
+                        (1, brr)
+                    };
+                    let clock = u64::from(clocks.$usartXclk().0);
+                    Bps(((samples * clock) / u64::from(usartdiv.max(1))) as u32)
+                }
+
+                /// Clears all receive errors
+                pub fn clear_errors(&mut self) {
+                    clear_errors(&self.usart)
+                }
+
+                /// Starts listening for the error interrupts
+                ///
+                /// The parity error interrupt is raised on every parity error,
+                /// the interrupt for framing, noise and overrun errors
+                /// only while the reception uses DMA.
+                pub fn listen_errors(&mut self) {
+                    self.usart.cr1.modify(|_, w| w.peie().set_bit());
+                    self.usart.cr3.modify(|_, w| w.eie().set_bit());
+                }
+
+                /// Stops listening for the error interrupts
+                pub fn unlisten_errors(&mut self) {
+                    self.usart.cr1.modify(|_, w| w.peie().clear_bit());
+                    self.usart.cr3.modify(|_, w| w.eie().clear_bit());
+                }
+
+                /// Sets the character, whose reception triggers [`Event::CharacterMatch`]
+                ///
+                /// The receiver is disabled for a moment to change the character.
+                pub fn set_match_character(&mut self, character: u8) {
+                    // The character can only be changed while the receiver is disabled
+                    self.usart.cr1.modify(|_, w| w.re().disabled());
+                    self.usart.cr2.modify(|_, w| w.add().bits(character));
+                    self.usart.cr1.modify(|_, w| w.re().enabled());
+                }
+
+                /// Sets the receiver timeout in bit durations, after which
+                /// [`Event::ReceiverTimeout`] is triggered, or disables the
+                /// timeout with `None`
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the timeout is greater than `0xff_ffff` bit durations.
+                pub fn set_receiver_timeout(&mut self, bits: Option<u32>) {
+                    match bits {
+                        Some(bits) => {
+                            crate::assert!(bits <= 0xff_ffff);
+                            self.usart.rtor.modify(|_, w| w.rto().bits(bits));
+                            self.usart.cr2.modify(|_, w| w.rtoen().enabled());
+                        }
+                        None => self.usart.cr2.modify(|_, w| w.rtoen().disabled()),
                     }
                 }
 
@@ -175,15 +1072,90 @@ macro_rules! hal {
                 }
 
                 /// Releases the USART peripheral and associated pins
-                pub fn free(self) -> ($USARTX, (TX, RX)) {
+                pub fn free(self) -> ($USARTX, PINS) {
                     (self.usart, self.pins)
                 }
             }
 
+            impl Rx<$USARTX> {
+                /// Returns `true`, if the flag of the event is set
+                ///
+                /// See [`Serial::is_event_triggered`].
+                pub fn is_event_triggered(&self, event: Event) -> bool {
+                    // NOTE(unsafe) atomic read with no side effects
+                    is_event_triggered(unsafe { &*$USARTX::ptr() }, event)
+                }
+
+                /// Clears the flag of the event
+                ///
+                /// See [`Serial::clear_event`].
+                pub fn clear_event(&mut self, event: Event) {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    clear_event(unsafe { &*$USARTX::ptr() }, event)
+                }
+
+                /// Returns the receive errors, which have occurred since
+                /// the errors were cleared the last time
+                pub fn errors(&self) -> Errors {
+                    // NOTE(unsafe) atomic read with no side effects
+                    Errors::read(unsafe { &*$USARTX::ptr() })
+                }
+
+                /// Clears all receive errors
+                pub fn clear_errors(&mut self) {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    clear_errors(unsafe { &*$USARTX::ptr() })
+                }
+
+                /// Starts listening for the error interrupts
+                ///
+                /// See [`Serial::listen_errors`].
+                pub fn listen_errors(&mut self) {
+                    // NOTE(unsafe) critical section prevents races
+                    cortex_m::interrupt::free(|_| unsafe {
+                        let usart = &*$USARTX::ptr();
+                        usart.cr1.modify(|_, w| w.peie().set_bit());
+                        usart.cr3.modify(|_, w| w.eie().set_bit());
+                    });
+                }
+
+                /// Stops listening for the error interrupts
+                pub fn unlisten_errors(&mut self) {
+                    // NOTE(unsafe) critical section prevents races
+                    cortex_m::interrupt::free(|_| unsafe {
+                        let usart = &*$USARTX::ptr();
+                        usart.cr1.modify(|_, w| w.peie().clear_bit());
+                        usart.cr3.modify(|_, w| w.eie().clear_bit());
+                    });
+                }
+            }
+
+            #[cfg(any(feature = "stm32f302", feature = "stm32f303"))]
+            impl<B, C: dma::Channel> dma::Transfer<B, C, Rx<$USARTX>> {
+                /// Returns the receive errors, which have occurred since
+                /// the errors were cleared the last time
+                ///
+                /// The DMA continues the transfer after a receive error,
+                /// so the received data may be corrupt, if this is not empty.
+                pub fn errors(&self) -> Errors {
+                    // NOTE(unsafe) atomic read with no side effects
+                    Errors::read(unsafe { &*$USARTX::ptr() })
+                }
+            }
+
             impl serial::Read<u8> for Rx<$USARTX> {
                 type Error = Error;
 
                 fn read(&mut self) -> nb::Result<u8, Error> {
+                    serial::Read::<u16>::read(self).map(|word| word as u8)
+                }
+            }
+
+            impl serial::Read<u16> for Rx<$USARTX> {
+                type Error = Error;
+
+                /// Reads a word of up to 9 bits
+                fn read(&mut self) -> nb::Result<u16, Error> {
                     // NOTE(unsafe) atomic read with no side effects
                     let isr = unsafe { (*$USARTX::ptr()).isr.read() };
 
@@ -220,6 +1192,19 @@ macro_rules! hal {
                 // our hardware configuration
                 type Error = Infallible;
 
+                fn flush(&mut self) -> nb::Result<(), Infallible> {
+                    serial::Write::<u16>::flush(self)
+                }
+
+                fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+                    serial::Write::<u16>::write(self, u16::from(byte))
+                }
+            }
+
+            impl serial::Write<u16> for Tx<$USARTX> {
+                // NOTE(Infallible) See the implementation of `serial::Write<u8>`
+                type Error = Infallible;
+
                 fn flush(&mut self) -> nb::Result<(), Infallible> {
                     // NOTE(unsafe) atomic read with no side effects
                     let isr = unsafe { (*$USARTX::ptr()).isr.read() };
@@ -231,15 +1216,16 @@ macro_rules! hal {
                     }
                 }
 
-                fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+                /// Writes a word of up to 9 bits
+                fn write(&mut self, word: u16) -> nb::Result<(), Infallible> {
                     // NOTE(unsafe) atomic read with no side effects
                     let isr = unsafe { (*$USARTX::ptr()).isr.read() };
 
                     if isr.txe().bit_is_set() {
                         // NOTE(unsafe) atomic write to stateless register
-                        // NOTE(write_volatile) 8-bit write that's not possible through the svd2rust API
+                        // NOTE(write_volatile) 16-bit write that's not possible through the svd2rust API
                         unsafe {
-                            ptr::write_volatile(&(*$USARTX::ptr()).tdr as *const _ as *mut _, byte)
+                            ptr::write_volatile(&(*$USARTX::ptr()).tdr as *const _ as *mut _, word)
                         }
                         Ok(())
                     } else {
@@ -250,9 +1236,174 @@ macro_rules! hal {
 
             impl blocking::serial::write::Default<u8> for Tx<$USARTX> {}
 
+            impl<PINS> Serial<$USARTX, PINS> {
+                /// Converts the `Serial` into an interrupt driven [`BufferedSerial`]
+                ///
+                /// One byte of each buffer stays unused. The receive interrupt
+                /// is enabled, the USART interrupt still has to be unmasked in the NVIC.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if one of the buffers is empty.
+                pub fn into_buffered(
+                    self,
+                    rx_buffer: &'static mut [u8],
+                    tx_buffer: &'static mut [u8],
+                ) -> BufferedSerial<$USARTX, PINS> {
+                    crate::assert!(!rx_buffer.is_empty() && !tx_buffer.is_empty());
+                    self.usart.cr1.modify(|_, w| w.rxneie().set_bit());
+                    BufferedSerial {
+                        serial: self,
+                        buffers: Buffers {
+                            rx: RingBuffer::new(rx_buffer),
+                            tx: RingBuffer::new(tx_buffer),
+                            errors: AtomicU8::new(0),
+                        },
+                    }
+                }
+            }
+
+            impl<PINS> BufferedSerial<$USARTX, PINS> {
+                /// Splits the `BufferedSerial` into its interrupt and application halves
+                ///
+                /// The interrupt half is usually moved to the interrupt handler,
+                /// which requires a `'static` borrow, e.g. from `cortex_m::singleton!`.
+                pub fn split(&mut self) -> (BufferedSerialIsr<'_, $USARTX>, BufferedSerialApp<'_, $USARTX>) {
+                    (
+                        BufferedSerialIsr { buffers: &self.buffers, _usart: PhantomData },
+                        BufferedSerialApp { buffers: &self.buffers, _usart: PhantomData },
+                    )
+                }
+
+                /// Disables the interrupts and releases the `Serial` and the buffers
+                pub fn release(self) -> (Serial<$USARTX, PINS>, (&'static mut [u8], &'static mut [u8])) {
+                    self.serial.usart.cr1.modify(|_, w| w.rxneie().clear_bit().txeie().clear_bit());
+                    (self.serial, (self.buffers.rx.release(), self.buffers.tx.release()))
+                }
+            }
+
+            impl BufferedSerialIsr<'_, $USARTX> {
+                /// Moves the received bytes into the receive buffer and the bytes
+                /// of the transmit buffer to the USART
+                ///
+                /// This has to be called from the interrupt handler of the USART.
+                /// Received bytes are dropped, if the receive buffer is full.
+                pub fn on_interrupt(&mut self) {
+                    // NOTE(unsafe) the USART is owned by the `BufferedSerial`, whose
+                    // application half only sets the TXEIE bit
+                    let usart = unsafe { &*$USARTX::ptr() };
+                    let isr = usart.isr.read();
+
+                    if isr.rxne().bit_is_set() {
+                        let byte = usart.rdr.read().rdr().bits() as u8;
+                        if !self.buffers.rx.push(byte) {
+                            self.buffers.errors.fetch_or(Error::Overrun.mask(), Ordering::Relaxed);
+                        }
+                    }
+
+                    let errors = (isr.bits() & 0b1111) as u8;
+                    if errors != 0 {
+                        self.buffers.errors.fetch_or(errors, Ordering::Relaxed);
+                        // Only the flags, which have been read, are cleared, so errors
+                        // occurring in the meantime are reported by the next interrupt.
+                        // NOTE(write): writing 0 to the other bits has no effect
+                        usart.icr.write(|w| {
+                            w.pecf().bit(isr.pe().bit_is_set());
+                            w.fecf().bit(isr.fe().bit_is_set());
+                            w.ncf().bit(isr.nf().bit_is_set());
+                            w.orecf().bit(isr.ore().bit_is_set())
+                        });
+                    }
+
+                    if isr.txe().bit_is_set() && usart.cr1.read().txeie().bit_is_set() {
+                        match self.buffers.tx.pop() {
+                            // NOTE(unsafe): any 8-bit value is a valid data word
+                            Some(byte) => usart.tdr.write(|w| unsafe { w.tdr().bits(u16::from(byte)) }),
+                            None => {
+                                usart.cr1.modify(|_, w| w.txeie().clear_bit());
+                                // The application half may have queued bytes and set
+                                // TXEIE in the meantime, which was just overwritten
+                                if !self.buffers.tx.is_empty() {
+                                    usart.cr1.modify(|_, w| w.txeie().set_bit());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            impl BufferedSerialApp<'_, $USARTX> {
+                /// Reads received bytes into `buffer` and returns the number of read bytes
+                pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+                    let mut count = 0;
+                    for byte in buffer.iter_mut() {
+                        match self.buffers.rx.pop() {
+                            Some(received) => *byte = received,
+                            None => break,
+                        }
+                        count += 1;
+                    }
+                    count
+                }
+
+                /// Queues the bytes of `data` for transmission and returns the
+                /// number of queued bytes, which is less than the length of `data`
+                /// if the transmit buffer is full
+                pub fn write(&mut self, data: &[u8]) -> usize {
+                    let mut count = 0;
+                    for &byte in data {
+                        if !self.buffers.tx.push(byte) {
+                            break;
+                        }
+                        count += 1;
+                    }
+                    if count > 0 {
+                        // NOTE(unsafe) the interrupt half only clears this bit,
+                        // when the transmit buffer is empty, see `on_interrupt`
+                        let usart = unsafe { &*$USARTX::ptr() };
+                        usart.cr1.modify(|_, w| w.txeie().set_bit());
+                    }
+                    count
+                }
+
+                /// Returns `true`, if all queued bytes have been transmitted
+                pub fn is_tx_complete(&self) -> bool {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let usart = unsafe { &*$USARTX::ptr() };
+                    self.buffers.tx.is_empty() && usart.isr.read().tc().bit_is_set()
+                }
+
+                /// Returns and clears the reception errors, which have occurred
+                ///
+                /// A full receive buffer is reported as [`Error::Overrun`].
+                pub fn take_errors(&mut self) -> Errors {
+                    Errors(self.buffers.errors.swap(0, Ordering::Relaxed))
+                }
+            }
+
+            impl fmt::Write for BufferedSerialApp<'_, $USARTX> {
+                /// Queues the string for transmission
+                ///
+                /// Returns an error, if the string does not fit into the transmit buffer.
+                /// The part of the string, which fits, is transmitted nevertheless.
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    if self.write(s.as_bytes()) == s.len() {
+                        Ok(())
+                    } else {
+                        Err(fmt::Error)
+                    }
+                }
+            }
+
             #[cfg(any(feature = "stm32f302", feature = "stm32f303"))]
             impl Rx<$USARTX> {
                 /// Fill the buffer with received data using DMA.
+                ///
+                /// To receive frames of unknown length, listen for [`Event::Idle`],
+                /// [`Event::CharacterMatch`] or [`Event::ReceiverTimeout`] and stop
+                /// the transfer on the event. The number of received bytes is the
+                /// length of the buffer minus the
+                /// [`remaining_transfers`](dma::Channel::remaining_transfers) of the channel.
                 pub fn read_exact<B, C>(
                     self,
                     buffer: B,
@@ -331,12 +1482,447 @@ macro_rules! hal {
                     });
                 }
             }
+
+            impl<PINS> Serial<$USARTX, PINS> {
+                /// Converts the `Serial` into a LIN node
+                ///
+                /// The frame format is changed to 8 data bits, no parity and 1 stop bit,
+                /// and the hardware flow control is disabled.
+                /// If no receiver timeout is set, the receiver timeout is set to the
+                /// maximum response time of a LIN frame, so that the receptions of the
+                /// [`Lin`] node can not block forever.
+                pub fn into_lin(mut self, break_length: BreakLength) -> Lin<$USARTX, PINS> {
+                    if self.usart.cr2.read().rtoen().is_disabled() {
+                        self.set_receiver_timeout(Some(LIN_RESPONSE_TIMEOUT));
+                    }
+                    // The LIN mode can only be enabled while the USART is disabled
+                    self.usart.cr1.modify(|_, w| w.ue().disabled());
+                    // NOTE(unsafe): M1 (bit 28) is not modeled by the PAC
+                    self.usart.cr1.modify(|r, w| unsafe {
+                        w.bits(r.bits() & !(1 << 28));
+                        w.m().bit8();
+                        w.pce().disabled()
+                    });
+                    self.usart.cr2.modify(|_, w| {
+                        match break_length {
+                            BreakLength::Bits10 => w.lbdl().bit10(),
+                            BreakLength::Bits11 => w.lbdl().bit11(),
+                        };
+                        w.stop().stop1();
+                        w.clken().disabled();
+                        w.linen().enabled()
+                    });
+                    // The hardware flow control is disabled, so that the transmitter
+                    // can not be stalled by the CTS input
+                    self.usart.cr3.modify(|_, w| {
+                        w.scen().disabled();
+                        w.hdsel().not_selected();
+                        w.iren().disabled();
+                        w.ctse().disabled();
+                        w.rtse().disabled()
+                    });
+                    self.usart.cr1.modify(|_, w| w.ue().enabled());
+
+                    Lin { serial: self }
+                }
+            }
+
+            impl<PINS> Lin<$USARTX, PINS> {
+                /// Sends the header of a frame as master, which is the break,
+                /// the sync field and the protected identifier
+                ///
+                /// Blocks until the header has been transmitted.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the identifier is greater than `63`.
+                pub fn send_header(&mut self, id: u8) {
+                    let usart = &self.serial.usart;
+                    while usart.isr.read().sbkf().bit_is_set() {}
+                    // NOTE(write): writing 0 to the other bits has no effect
+                    usart.rqr.write(|w| w.sbkrq().break_());
+                    self.write_byte(0x55);
+                    self.write_byte(protected_id(id));
+                    self.discard_echo();
+                }
+
+                /// Sends the response of a frame with up to 8 data bytes and the checksum
+                ///
+                /// Blocks until the response has been transmitted.
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the identifier is greater than `63`
+                /// or if there are more than 8 data bytes.
+                pub fn write_response(&mut self, id: u8, data: &[u8], model: Checksum) {
+                    crate::assert!(data.len() <= 8);
+                    let checksum = checksum(protected_id(id), data, model);
+                    for &byte in data {
+                        self.write_byte(byte);
+                    }
+                    self.write_byte(checksum);
+                    self.discard_echo();
+                }
+
+                /// Receives the response of a frame into `buffer` and verifies the checksum
+                ///
+                /// Blocks until the response has been received or the receiver timeout
+                /// has elapsed after a byte, which is reported as [`LinError::Timeout`].
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the identifier is greater than `63`
+                /// or if the buffer is longer than 8 bytes.
+                pub fn read_response(&mut self, id: u8, buffer: &mut [u8], model: Checksum) -> Result<(), LinError> {
+                    crate::assert!(buffer.len() <= 8);
+                    for byte in buffer.iter_mut() {
+                        *byte = self.read_byte()?;
+                    }
+                    if self.read_byte()? == checksum(protected_id(id), buffer, model) {
+                        Ok(())
+                    } else {
+                        Err(LinError::Checksum)
+                    }
+                }
+
+                /// Receives the rest of the header as slave, after a break has been
+                /// detected, and returns the identifier of the frame
+                ///
+                /// Blocks until the header has been received or the receiver timeout
+                /// has elapsed after a byte, which is reported as [`LinError::Timeout`].
+                pub fn read_header(&mut self) -> Result<u8, LinError> {
+                    self.discard_break();
+                    if self.read_byte()? != 0x55 {
+                        return Err(LinError::Sync);
+                    }
+                    let pid = self.read_byte()?;
+                    let id = pid & 0b11_1111;
+                    if pid == protected_id(id) {
+                        Ok(id)
+                    } else {
+                        Err(LinError::IdParity)
+                    }
+                }
+
+                /// Returns `true`, if a break has been detected
+                pub fn is_break_detected(&self) -> bool {
+                    self.serial.usart.isr.read().lbdf().bit_is_set()
+                }
+
+                /// Clears the break detection flag
+                pub fn clear_break_detected(&mut self) {
+                    // NOTE(write): writing 0 to the other bits has no effect
+                    self.serial.usart.icr.write(|w| w.lbdcf().clear());
+                }
+
+                /// Starts listening for the break detection interrupt
+                pub fn listen_break(&mut self) {
+                    self.serial.usart.cr2.modify(|_, w| w.lbdie().enabled());
+                }
+
+                /// Stops listening for the break detection interrupt
+                pub fn unlisten_break(&mut self) {
+                    self.serial.usart.cr2.modify(|_, w| w.lbdie().disabled());
+                }
+
+                /// Disables the LIN mode and releases the `Serial`
+                pub fn release(self) -> Serial<$USARTX, PINS> {
+                    let usart = &self.serial.usart;
+                    usart.cr1.modify(|_, w| w.ue().disabled());
+                    usart.cr2.modify(|_, w| w.linen().disabled().lbdie().disabled());
+                    usart.cr1.modify(|_, w| w.ue().enabled());
+                    self.serial
+                }
+
+                fn write_byte(&mut self, byte: u8) {
+                    let usart = &self.serial.usart;
+                    while usart.isr.read().txe().bit_is_clear() {}
+                    // NOTE(unsafe): any 8-bit value is a valid data word
+                    usart.tdr.write(|w| unsafe { w.tdr().bits(u16::from(byte)) });
+                }
+
+                fn read_byte(&mut self) -> Result<u8, LinError> {
+                    let usart = &self.serial.usart;
+                    loop {
+                        let isr = usart.isr.read();
+                        let error = if isr.pe().bit_is_set() {
+                            Error::Parity
+                        } else if isr.fe().bit_is_set() {
+                            Error::Framing
+                        } else if isr.nf().bit_is_set() {
+                            Error::Noise
+                        } else if isr.ore().bit_is_set() {
+                            Error::Overrun
+                        } else if isr.rxne().bit_is_set() {
+                            return Ok(usart.rdr.read().rdr().bits() as u8);
+                        } else if isr.rtof().bit_is_set() {
+                            // NOTE(write): writing 0 to the other bits has no effect
+                            usart.icr.write(|w| w.rtocf().clear());
+                            return Err(LinError::Timeout);
+                        } else {
+                            continue;
+                        };
+                        clear_errors(usart);
+                        return Err(LinError::Serial(error));
+                    }
+                }
+
+                /// Waits until the transmission is complete and discards the echo
+                fn discard_echo(&mut self) {
+                    while self.serial.usart.isr.read().tc().bit_is_clear() {}
+                    self.discard_received();
+                }
+
+                /// Discards the received break and clears the break detection and
+                /// receiver timeout flags
+                ///
+                /// The break is received as zero byte with a framing error. A byte
+                /// received without framing error, e.g. the sync byte, is kept.
+                fn discard_break(&mut self) {
+                    let usart = &self.serial.usart;
+                    let isr = usart.isr.read();
+                    if isr.rxne().bit_is_set() && isr.fe().bit_is_set() {
+                        // NOTE(write): writing 0 to the other bits has no effect
+                        usart.rqr.write(|w| w.rxfrq().discard());
+                    }
+                    // A receiver timeout before the break is outdated
+                    // NOTE(write): writing 0 to the other bits has no effect
+                    usart.icr.write(|w| w.fecf().clear().lbdcf().clear().rtocf().clear());
+                }
+
+                /// Discards the received data, the errors and the break detection flag
+                fn discard_received(&mut self) {
+                    let usart = &self.serial.usart;
+                    // NOTE(write): writing 0 to the other bits has no effect
+                    usart.rqr.write(|w| w.rxfrq().discard());
+                    clear_errors(usart);
+                    // NOTE(write): writing 0 to the other bits has no effect
+                    usart.icr.write(|w| w.lbdcf().clear().rtocf().clear());
+                }
+            }
         )+
     }
 }
 
 hal! {
-    USART1: (usart1, APB2, usart1en, usart1rst, pclk2),
-    USART2: (usart2, APB1, usart2en, usart2rst, pclk1),
-    USART3: (usart3, APB1, usart3en, usart3rst, pclk1),
+    USART1: (usart1, APB2, usart1en, usart1rst, usart1clk),
+    USART2: (usart2, APB1, usart2en, usart2rst, usart2clk),
+    USART3: (usart3, APB1, usart3en, usart3rst, usart3clk),
+}
+
+// UART4 and UART5 do not support the auto baud rate detection
+macro_rules! auto_baudrate {
+    ($($USARTX:ident,)+) => {
+        $(
+            impl<PINS> Serial<$USARTX, PINS> {
+                /// Enables the auto baud rate detection, which measures
+                /// the baud rate on the first received character
+                ///
+                /// The first character is received as usual, if the detection succeeds.
+                pub fn enable_auto_baudrate(&mut self, mode: AutoBaudRateMode) {
+                    // The mode can only be changed while the USART is disabled
+                    self.usart.cr1.modify(|_, w| w.ue().disabled());
+                    self.usart.cr2.modify(|_, w| {
+                        match mode {
+                            AutoBaudRateMode::StartBit => w.abrmod().start(),
+                            AutoBaudRateMode::FallingEdge => w.abrmod().edge(),
+                            AutoBaudRateMode::Frame0x7F => w.abrmod().frame7f(),
+                            AutoBaudRateMode::Frame0x55 => w.abrmod().frame55(),
+                        };
+                        w.abren().enabled()
+                    });
+                    self.usart.cr1.modify(|_, w| w.ue().enabled());
+                }
+
+                /// Disables the auto baud rate detection
+                pub fn disable_auto_baudrate(&mut self) {
+                    self.usart.cr1.modify(|_, w| w.ue().disabled());
+                    self.usart.cr2.modify(|_, w| w.abren().disabled());
+                    self.usart.cr1.modify(|_, w| w.ue().enabled());
+                }
+
+                /// Restarts the auto baud rate detection on the next received character
+                pub fn request_auto_baudrate(&mut self) {
+                    // NOTE(write): writing 0 to the other bits has no effect
+                    self.usart.rqr.write(|w| w.abrrq().request());
+                }
+
+                /// Returns `Ok`, if the auto baud rate detection has completed
+                /// and the detected baud rate is returned by [`baud_rate`](Self::baud_rate)
+                ///
+                /// Returns [`AutoBaudRateError`], if the detection failed, e.g. because
+                /// the baud rate is out of range or the character does not match the mode.
+                pub fn auto_baudrate(&self) -> nb::Result<(), AutoBaudRateError> {
+                    let isr = self.usart.isr.read();
+                    if isr.abrf().bit_is_clear() {
+                        Err(nb::Error::WouldBlock)
+                    } else if isr.abre().bit_is_set() {
+                        Err(nb::Error::Other(AutoBaudRateError))
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        )+
+    };
+}
+
+auto_baudrate! {
+    USART1,
+    USART2,
+    USART3,
+}
+
+// The wake-up from Stop mode is supported by USART1 on all parts
+// and by the other U(S)ARTs only on the STM32F302xD/E, STM32F303xD/E and STM32F398
+macro_rules! wakeup_from_stop {
+    ($($USARTX:ident,)+) => {
+        $(
+            impl<PINS> Serial<$USARTX, PINS> {
+                /// Enables the wake-up from Stop mode on activity on the receive line
+                ///
+                /// Listen for [`Event::WakeupFromStop`] to raise the interrupt of the
+                /// USART, whose EXTI line is unmasked after reset, and enter the Stop mode
+                /// with [`Pwr::enter_stop`](crate::pwr::Pwr::enter_stop).
+                ///
+                /// The USART has to be clocked by the HSI or the LSE (see
+                /// [`UsartClockSource`](crate::rcc::UsartClockSource)), as the other clocks
+                /// are stopped in Stop mode.
+                pub fn enable_wakeup_from_stop(&mut self, source: WakeupSource) {
+                    // The wake-up source can only be changed while the USART is disabled
+                    self.usart.cr1.modify(|_, w| w.ue().disabled());
+                    self.usart.cr3.modify(|_, w| match source {
+                        WakeupSource::AddressMatch => w.wus().address(),
+                        WakeupSource::StartBit => w.wus().start(),
+                        WakeupSource::Rxne => w.wus().rxne(),
+                    });
+                    self.usart.cr1.modify(|_, w| w.ue().enabled().uesm().enabled());
+                }
+
+                /// Disables the wake-up from Stop mode
+                pub fn disable_wakeup_from_stop(&mut self) {
+                    self.usart.cr1.modify(|_, w| w.uesm().disabled());
+                }
+            }
+        )+
+    };
+}
+
+wakeup_from_stop! {
+    USART1,
+}
+
+#[cfg(any(
+    feature = "stm32f302xd",
+    feature = "stm32f302xe",
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+    feature = "stm32f398"
+))]
+wakeup_from_stop! {
+    USART2,
+    USART3,
+    UART4,
+    UART5,
+}
+
+#[cfg(any(
+    feature = "stm32f302xb",
+    feature = "stm32f302xc",
+    feature = "stm32f302xd",
+    feature = "stm32f302xe",
+    feature = "stm32f303xb",
+    feature = "stm32f303xc",
+    feature = "stm32f303xd",
+    feature = "stm32f303xe",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+hal! {
+    UART4: (uart4, APB1, uart4en, uart4rst, uart4clk),
+    UART5: (uart5, APB1, uart5en, uart5rst, uart5clk),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brr(baud_rate: u32, oversampling: Oversampling, clock: u32) -> Result<u32, ConfigError> {
+        Config::default()
+            .baud_rate(Bps(baud_rate))
+            .oversampling(oversampling)
+            .brr(Hertz(clock))
+    }
+
+    #[test]
+    fn brr_oversampling_by_16() {
+        assert_eq!(brr(115_200, Oversampling::By16, 72_000_000).unwrap(), 625);
+        // 833.33 is rounded down
+        assert_eq!(brr(9_600, Oversampling::By16, 8_000_000).unwrap(), 833);
+        // 69.44 and 17.36 are rounded down
+        assert_eq!(brr(115_200, Oversampling::By16, 8_000_000).unwrap(), 69);
+        assert_eq!(brr(460_800, Oversampling::By16, 8_000_000).unwrap(), 17);
+        // 138.89 is rounded up
+        assert_eq!(brr(57_600, Oversampling::By16, 8_000_000).unwrap(), 139);
+        assert_eq!(brr(4_500_000, Oversampling::By16, 72_000_000).unwrap(), 16);
+    }
+
+    #[test]
+    fn brr_oversampling_by_8() {
+        // USARTDIV 1250 = 0x4e2, BRR[2:0] is USARTDIV[3:0] shifted right
+        assert_eq!(brr(115_200, Oversampling::By8, 72_000_000).unwrap(), 0x4e1);
+        // USARTDIV 1667 = 0x683, BRR[3] is kept cleared
+        assert_eq!(brr(9_600, Oversampling::By8, 8_000_000).unwrap(), 0x681);
+        assert_eq!(brr(9_000_000, Oversampling::By8, 72_000_000).unwrap(), 0x10);
+    }
+
+    #[test]
+    fn brr_out_of_range() {
+        assert!(matches!(
+            brr(9_000_000, Oversampling::By16, 72_000_000),
+            Err(ConfigError::BaudRateTooHigh)
+        ));
+        assert!(matches!(
+            brr(10_000_000, Oversampling::By8, 72_000_000),
+            Err(ConfigError::BaudRateTooHigh)
+        ));
+        assert!(matches!(
+            brr(1_000, Oversampling::By16, 72_000_000),
+            Err(ConfigError::BaudRateTooLow)
+        ));
+        assert!(matches!(
+            brr(0, Oversampling::By16, 72_000_000),
+            Err(ConfigError::BaudRateTooLow)
+        ));
+    }
+
+    #[test]
+    fn lin_protected_id() {
+        assert_eq!(protected_id(0x00), 0x80);
+        assert_eq!(protected_id(0x01), 0xc1);
+        assert_eq!(protected_id(0x10), 0x50);
+        // Identifiers of the diagnostic frames
+        assert_eq!(protected_id(0x3c), 0x3c);
+        assert_eq!(protected_id(0x3d), 0x7d);
+        assert_eq!(protected_id(0x3f), 0xbf);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lin_protected_id_out_of_range() {
+        protected_id(0x40);
+    }
+
+    #[test]
+    fn lin_checksum() {
+        // Example of the LIN specification
+        let data = [0x4a, 0x55, 0x93, 0xe5];
+        assert_eq!(checksum(0x50, &data, Checksum::Classic), 0xe6);
+        assert_eq!(checksum(0x50, &data, Checksum::Enhanced), 0x96);
+        assert_eq!(checksum(0x50, &[], Checksum::Classic), 0xff);
+        assert_eq!(checksum(0x50, &[], Checksum::Enhanced), !0x50);
+        // The carries are added to the sum again
+        assert_eq!(checksum(0x80, &[0xff; 8], Checksum::Classic), 0x00);
+        assert_eq!(checksum(0xff, &[0x01], Checksum::Enhanced), 0xfe);
+    }
 }
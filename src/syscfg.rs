@@ -0,0 +1,58 @@
+//! System configuration controller
+//!
+//! The SYSCFG peripheral is mainly needed to route GPIO pins to the
+//! external interrupt lines. See [`ExtiPin`](crate::gpio::ExtiPin).
+
+use crate::pac::{syscfg, SYSCFG};
+use crate::rcc::APB2;
+
+/// Extension trait to constrain the SYSCFG peripheral
+pub trait SysCfgExt {
+    /// Constrains the SYSCFG peripheral to play nicely with the other abstractions
+    fn constrain(self, apb2: &mut APB2) -> SysCfg;
+}
+
+impl SysCfgExt for SYSCFG {
+    fn constrain(self, apb2: &mut APB2) -> SysCfg {
+        apb2.enr().modify(|_, w| w.syscfgen().set_bit());
+
+        SysCfg { _0: () }
+    }
+}
+
+/// Constrained SYSCFG peripheral
+///
+/// An instance of this struct is acquired by calling the
+/// [`constrain`](SysCfgExt::constrain) function on the
+/// [`SYSCFG`](crate::pac::SYSCFG) struct.
+///
+/// ```
+/// let dp = pac::Peripherals::take().unwrap();
+/// let mut rcc = dp.RCC.constrain();
+/// let mut syscfg = dp.SYSCFG.constrain(&mut rcc.apb2);
+/// ```
+pub struct SysCfg {
+    _0: (),
+}
+
+impl SysCfg {
+    pub(crate) fn exticr1(&mut self) -> &syscfg::EXTICR1 {
+        // NOTE(unsafe) this proxy grants exclusive access to this register
+        unsafe { &(*SYSCFG::ptr()).exticr1 }
+    }
+
+    pub(crate) fn exticr2(&mut self) -> &syscfg::EXTICR2 {
+        // NOTE(unsafe) this proxy grants exclusive access to this register
+        unsafe { &(*SYSCFG::ptr()).exticr2 }
+    }
+
+    pub(crate) fn exticr3(&mut self) -> &syscfg::EXTICR3 {
+        // NOTE(unsafe) this proxy grants exclusive access to this register
+        unsafe { &(*SYSCFG::ptr()).exticr3 }
+    }
+
+    pub(crate) fn exticr4(&mut self) -> &syscfg::EXTICR4 {
+        // NOTE(unsafe) this proxy grants exclusive access to this register
+        unsafe { &(*SYSCFG::ptr()).exticr4 }
+    }
+}
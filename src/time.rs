@@ -1,29 +1,43 @@
 //! Time units
 
+use core::convert::TryFrom;
+
 use cortex_m::peripheral::DWT;
 
 use crate::rcc::Clocks;
 
 /// Bits per second
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct Bps(pub u32);
 
 /// Hertz
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct Hertz(pub u32);
 
 /// KiloHertz
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct KiloHertz(pub u32);
 
 /// MegaHertz
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct MegaHertz(pub u32);
 
 /// Time unit
-#[derive(PartialEq, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Seconds(pub u32);
+
+/// Time unit
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct MilliSeconds(pub u32);
 
+/// Time unit
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct MicroSeconds(pub u32);
+
+/// Time unit
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct NanoSeconds(pub u32);
+
 /// Extension trait that adds convenience methods to the `u32` type
 pub trait U32Ext {
     /// Wrap in `Bps`
@@ -40,6 +54,15 @@ pub trait U32Ext {
 
     /// Wrap in `MilliSeconds`
     fn ms(self) -> MilliSeconds;
+
+    /// Wrap in `Seconds`
+    fn s(self) -> Seconds;
+
+    /// Wrap in `MicroSeconds`
+    fn us(self) -> MicroSeconds;
+
+    /// Wrap in `NanoSeconds`
+    fn ns(self) -> NanoSeconds;
 }
 
 impl U32Ext for u32 {
@@ -59,9 +82,21 @@ impl U32Ext for u32 {
         MegaHertz(self)
     }
 
+    fn s(self) -> Seconds {
+        Seconds(self)
+    }
+
     fn ms(self) -> MilliSeconds {
         MilliSeconds(self)
     }
+
+    fn us(self) -> MicroSeconds {
+        MicroSeconds(self)
+    }
+
+    fn ns(self) -> NanoSeconds {
+        NanoSeconds(self)
+    }
 }
 
 impl From<KiloHertz> for Hertz {
@@ -82,6 +117,130 @@ impl From<MegaHertz> for KiloHertz {
     }
 }
 
+/// Error of a conversion to a finer time unit, whose value does not fit into `u32`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+impl TryFrom<Seconds> for MilliSeconds {
+    type Error = Overflow;
+
+    fn try_from(val: Seconds) -> Result<Self, Self::Error> {
+        val.0.checked_mul(1_000).map(Self).ok_or(Overflow)
+    }
+}
+
+impl TryFrom<Seconds> for MicroSeconds {
+    type Error = Overflow;
+
+    fn try_from(val: Seconds) -> Result<Self, Self::Error> {
+        val.0.checked_mul(1_000_000).map(Self).ok_or(Overflow)
+    }
+}
+
+impl TryFrom<Seconds> for NanoSeconds {
+    type Error = Overflow;
+
+    fn try_from(val: Seconds) -> Result<Self, Self::Error> {
+        val.0.checked_mul(1_000_000_000).map(Self).ok_or(Overflow)
+    }
+}
+
+impl TryFrom<MilliSeconds> for MicroSeconds {
+    type Error = Overflow;
+
+    fn try_from(val: MilliSeconds) -> Result<Self, Self::Error> {
+        val.0.checked_mul(1_000).map(Self).ok_or(Overflow)
+    }
+}
+
+impl TryFrom<MilliSeconds> for NanoSeconds {
+    type Error = Overflow;
+
+    fn try_from(val: MilliSeconds) -> Result<Self, Self::Error> {
+        val.0.checked_mul(1_000_000).map(Self).ok_or(Overflow)
+    }
+}
+
+impl TryFrom<MicroSeconds> for NanoSeconds {
+    type Error = Overflow;
+
+    fn try_from(val: MicroSeconds) -> Result<Self, Self::Error> {
+        val.0.checked_mul(1_000).map(Self).ok_or(Overflow)
+    }
+}
+
+macro_rules! checked_arithmetic {
+    ($($Unit:ident),+) => {
+        $(
+            impl $Unit {
+                /// Checked addition, returns `None` on overflow
+                pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                    self.0.checked_add(rhs.0).map(Self)
+                }
+
+                /// Checked subtraction, returns `None` on underflow
+                pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    self.0.checked_sub(rhs.0).map(Self)
+                }
+
+                /// Checked multiplication with a scalar, returns `None` on overflow
+                pub fn checked_mul(self, rhs: u32) -> Option<Self> {
+                    self.0.checked_mul(rhs).map(Self)
+                }
+
+                /// Checked division by a scalar, returns `None` if `rhs` is zero
+                pub fn checked_div(self, rhs: u32) -> Option<Self> {
+                    self.0.checked_div(rhs).map(Self)
+                }
+            }
+        )+
+    };
+}
+
+checked_arithmetic!(
+    Bps,
+    Hertz,
+    KiloHertz,
+    MegaHertz,
+    Seconds,
+    MilliSeconds,
+    MicroSeconds,
+    NanoSeconds
+);
+
+impl Hertz {
+    /// Returns the period of one cycle of this frequency
+    ///
+    /// The period is truncated to whole nanoseconds.
+    /// Returns `None` if the frequency is zero.
+    pub fn period(self) -> Option<NanoSeconds> {
+        1_000_000_000_u32.checked_div(self.0).map(NanoSeconds)
+    }
+}
+
+macro_rules! frequency {
+    ($($Unit:ident: $per_second:expr,)+) => {
+        $(
+            impl $Unit {
+                /// Returns the frequency, which has this duration as its period
+                ///
+                /// The frequency is truncated to whole hertz.
+                /// Returns `None` if the duration is zero.
+                pub fn frequency(self) -> Option<Hertz> {
+                    ($per_second as u32).checked_div(self.0).map(Hertz)
+                }
+            }
+        )+
+    };
+}
+
+frequency! {
+    Seconds: 1,
+    MilliSeconds: 1_000,
+    MicroSeconds: 1_000_000,
+    NanoSeconds: 1_000_000_000,
+}
+
 /// A monotonic nondecreasing timer
 #[derive(Clone, Copy)]
 pub struct MonoTimer {
@@ -125,4 +284,78 @@ impl Instant {
     pub fn elapsed(self) -> u32 {
         DWT::get_cycle_count().wrapping_sub(self.now)
     }
+
+    /// Time elapsed since the `Instant` was created
+    ///
+    /// The monotonic timer has to be the one, which created this `Instant`.
+    pub fn elapsed_time(self, timer: MonoTimer) -> MicroSeconds {
+        let ticks = u64::from(self.elapsed());
+        MicroSeconds((ticks * 1_000_000 / u64::from(timer.frequency().0)) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_to_finer_durations() {
+        assert_eq!(MilliSeconds::try_from(Seconds(2)), Ok(MilliSeconds(2_000)));
+        assert_eq!(
+            MicroSeconds::try_from(Seconds(2)),
+            Ok(MicroSeconds(2_000_000))
+        );
+        assert_eq!(
+            NanoSeconds::try_from(Seconds(4)),
+            Ok(NanoSeconds(4_000_000_000))
+        );
+        assert_eq!(NanoSeconds::try_from(Seconds(5)), Err(Overflow));
+        assert_eq!(
+            MicroSeconds::try_from(MilliSeconds(4_294_967)),
+            Ok(MicroSeconds(4_294_967_000))
+        );
+        assert_eq!(
+            MicroSeconds::try_from(MilliSeconds(4_294_968)),
+            Err(Overflow)
+        );
+        assert_eq!(
+            NanoSeconds::try_from(MicroSeconds(4_294_968)),
+            Err(Overflow)
+        );
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(
+            MilliSeconds(1).checked_add(MilliSeconds(2)),
+            Some(MilliSeconds(3))
+        );
+        assert_eq!(MilliSeconds(u32::MAX).checked_add(MilliSeconds(1)), None);
+        assert_eq!(Hertz(5).checked_sub(Hertz(2)), Some(Hertz(3)));
+        assert_eq!(Hertz(2).checked_sub(Hertz(5)), None);
+        assert_eq!(Seconds(3).checked_mul(4), Some(Seconds(12)));
+        assert_eq!(NanoSeconds(u32::MAX).checked_mul(2), None);
+        assert_eq!(MicroSeconds(10).checked_div(3), Some(MicroSeconds(3)));
+        assert_eq!(MicroSeconds(10).checked_div(0), None);
+    }
+
+    #[test]
+    fn period() {
+        assert_eq!(Hertz(1).period(), Some(NanoSeconds(1_000_000_000)));
+        assert_eq!(Hertz(1_000).period(), Some(NanoSeconds(1_000_000)));
+        // 333.33 ns are truncated
+        assert_eq!(Hertz(3_000_000).period(), Some(NanoSeconds(333)));
+        assert_eq!(Hertz(2_000_000_000).period(), Some(NanoSeconds(0)));
+        assert_eq!(Hertz(0).period(), None);
+    }
+
+    #[test]
+    fn frequency() {
+        assert_eq!(Seconds(1).frequency(), Some(Hertz(1)));
+        assert_eq!(MilliSeconds(3).frequency(), Some(Hertz(333)));
+        assert_eq!(MicroSeconds(1).frequency(), Some(Hertz(1_000_000)));
+        assert_eq!(NanoSeconds(1_000).frequency(), Some(Hertz(1_000_000)));
+        assert_eq!(Seconds(2).frequency(), Some(Hertz(0)));
+        assert_eq!(MilliSeconds(0).frequency(), None);
+    }
 }
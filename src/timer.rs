@@ -2,6 +2,7 @@
 
 use core::convert::{From, TryFrom};
 
+use crate::hal::blocking::delay::{DelayMs, DelayUs};
 use crate::hal::timer::{CountDown, Periodic};
 #[cfg(any(
     feature = "stm32f301",
@@ -54,7 +55,7 @@ use crate::pac::{TIM3, TIM7};
 use void::Void;
 
 use crate::rcc::{Clocks, APB1, APB2};
-use crate::time::Hertz;
+use crate::time::{Hertz, KiloHertz, MegaHertz, MicroSeconds, MilliSeconds, NanoSeconds, Seconds};
 
 /// Associated clocks with timers
 pub trait PclkSrc {
@@ -68,12 +69,413 @@ pub struct Timer<TIM> {
     tim: TIM,
 }
 
+/// Hardware timer as a delay provider
+///
+/// Unlike [`delay::Delay`](crate::delay::Delay), this does not
+/// occupy the system timer (SysTick), which is e.g. used by schedulers.
+///
+/// ```
+/// let timer = Timer::tim2(dp.TIM2, 1.hz(), clocks, &mut rcc.apb1);
+/// let mut delay = Delay::new(timer);
+/// delay.delay_ms(1_000u32);
+/// ```
+pub struct Delay<TIM> {
+    timer: Timer<TIM>,
+}
+
+/// Source of the trigger output (TRGO) of a master timer
+#[derive(Clone, Copy)]
+pub enum MasterMode {
+    /// The reset of the counter (UG bit) is the trigger output
+    Reset,
+    /// The counter enable signal (CEN bit) is the trigger output
+    Enable,
+    /// The update event is the trigger output
+    Update,
+    /// A capture or compare match of channel 1 is the trigger output
+    ComparePulse,
+    /// The output compare signal of channel 1 is the trigger output
+    Compare1,
+    /// The output compare signal of channel 2 is the trigger output
+    Compare2,
+    /// The output compare signal of channel 3 is the trigger output
+    Compare3,
+    /// The output compare signal of channel 4 is the trigger output
+    Compare4,
+}
+
+impl MasterMode {
+    /// Value of the MMS bits of the TIMx_CR2 register
+    pub(crate) fn bits(self) -> u32 {
+        match self {
+            MasterMode::Reset => 0b000,
+            MasterMode::Enable => 0b001,
+            MasterMode::Update => 0b010,
+            MasterMode::ComparePulse => 0b011,
+            MasterMode::Compare1 => 0b100,
+            MasterMode::Compare2 => 0b101,
+            MasterMode::Compare3 => 0b110,
+            MasterMode::Compare4 => 0b111,
+        }
+    }
+}
+
+/// Reaction of a slave timer on the trigger output of its master timer
+#[derive(Clone, Copy)]
+pub enum SlaveMode {
+    /// The counter is reset on the rising edge of the trigger
+    Reset,
+    /// The counter is only running, while the trigger is high
+    Gated,
+    /// The counter is started on the rising edge of the trigger
+    Trigger,
+    /// The counter counts the rising edges of the trigger
+    ExternalClock,
+}
+
+impl SlaveMode {
+    /// Value of the SMS bits of the TIMx_SMCR register
+    pub(crate) fn bits(self) -> u32 {
+        match self {
+            SlaveMode::Reset => 0b100,
+            SlaveMode::Gated => 0b101,
+            SlaveMode::Trigger => 0b110,
+            SlaveMode::ExternalClock => 0b111,
+        }
+    }
+}
+
+/// The trigger output of this (master) timer is connected to an internal
+/// trigger input (ITRx) of the slave timer `SLAVE`
+///
+/// This trait is used to check the connection between master and slave
+/// timers at compile time and is implemented according to the internal
+/// trigger connection tables of the reference manual.
+///
+/// # Safety
+///
+/// `ITR` has to be the internal trigger input of the slave timer, which is
+/// connected to this timer, or the slave timer reacts on another timer.
+pub unsafe trait InternalTrigger<SLAVE> {
+    /// Number `x` of the internal trigger input ITRx of the slave timer
+    const ITR: u8;
+}
+
+/// Timeout of a [`CountDown`] timer, given as frequency or as duration
+#[derive(Clone, Copy)]
+pub enum Timeout {
+    /// The timer times out with the given frequency
+    ///
+    /// A frequency of zero never times out and is rejected with [`Error::TooLong`].
+    Frequency(Hertz),
+    /// The timer times out after the given number of nanoseconds
+    ///
+    /// This holds any of the duration units without overflow.
+    Duration(u64),
+}
+
+impl Timeout {
+    /// Number of timer clock ticks of the timeout, rounded to the nearest tick
+    ///
+    /// Saturates at `u64::MAX`, which can not be reached by any timer.
+    pub(crate) fn ticks(self, timer_clock: Hertz) -> u64 {
+        let clock = u64::from(timer_clock.0);
+        match self {
+            Timeout::Frequency(Hertz(frequency)) => {
+                let frequency = u64::from(frequency);
+                (clock + frequency / 2)
+                    .checked_div(frequency)
+                    .unwrap_or(u64::MAX)
+            }
+            Timeout::Duration(ns) => {
+                // Whole seconds and the remainder are scaled separately,
+                // as `clock * ns` overflows for long durations
+                let seconds = ns / 1_000_000_000;
+                let rest = ns % 1_000_000_000;
+                seconds
+                    .saturating_mul(clock)
+                    .saturating_add((rest * clock + 500_000_000) / 1_000_000_000)
+            }
+        }
+    }
+}
+
+impl From<Hertz> for Timeout {
+    fn from(val: Hertz) -> Self {
+        Timeout::Frequency(val)
+    }
+}
+
+impl From<KiloHertz> for Timeout {
+    fn from(val: KiloHertz) -> Self {
+        Timeout::Frequency(val.into())
+    }
+}
+
+impl From<MegaHertz> for Timeout {
+    fn from(val: MegaHertz) -> Self {
+        Timeout::Frequency(val.into())
+    }
+}
+
+impl From<NanoSeconds> for Timeout {
+    fn from(val: NanoSeconds) -> Self {
+        Timeout::Duration(u64::from(val.0))
+    }
+}
+
+impl From<MicroSeconds> for Timeout {
+    fn from(val: MicroSeconds) -> Self {
+        Timeout::Duration(u64::from(val.0) * 1_000)
+    }
+}
+
+impl From<MilliSeconds> for Timeout {
+    fn from(val: MilliSeconds) -> Self {
+        Timeout::Duration(u64::from(val.0) * 1_000_000)
+    }
+}
+
+impl From<Seconds> for Timeout {
+    fn from(val: Seconds) -> Self {
+        Timeout::Duration(u64::from(val.0) * 1_000_000_000)
+    }
+}
+
+/// Error of the prescaler and auto-reload calculation
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The timeout is too short for the timer clock
+    TooShort,
+    /// The timeout is too long for the prescaler and the counter of the timer
+    TooLong,
+}
+
+/// Calculates the prescaler (PSC) and auto-reload (ARR) register values
+/// for a period of `ticks` timer clock ticks.
+///
+/// The smallest possible prescaler is chosen, as this gives the finest
+/// resolution of the auto-reload value, and the auto-reload value is
+/// rounded to the nearest value. This keeps the error of the period
+/// below half a prescaled tick.
+pub(crate) fn prescaler_and_auto_reload(ticks: u64, max_arr: u32) -> Result<(u16, u32), Error> {
+    // The counter needs at least two states to produce update events
+    if ticks < 2 {
+        return Err(Error::TooShort);
+    }
+
+    let max_period = u64::from(max_arr) + 1;
+    let prescaler = (ticks - 1) / max_period + 1;
+    let psc = u16::try_from(prescaler - 1).map_err(|_| Error::TooLong)?;
+    let period = (ticks + prescaler / 2) / prescaler;
+    // `period` is at most `max_period`, so this can not overflow
+    Ok((psc, (period - 1) as u32))
+}
+
+/// Calculates the prescaler (PSC) register value for a period of
+/// `ticks` timer clock ticks, when the counter counts `counts` times per period.
+///
+/// The prescaler is rounded to the nearest value.
+pub(crate) fn prescaler(ticks: u64, counts: u64) -> Result<u16, Error> {
+    let prescaler = ticks.saturating_add(counts / 2) / counts;
+    if prescaler == 0 {
+        return Err(Error::TooShort);
+    }
+    u16::try_from(prescaler - 1).map_err(|_| Error::TooLong)
+}
+
 /// Interrupt events
 pub enum Event {
     /// Timer timed out / count down ended
     Update,
 }
 
+impl<TIM> Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    /// Configures a hardware timer as a delay provider
+    ///
+    /// The timer counts in steps of one microsecond, so the timer clock
+    /// should be a whole number of megahertz. Otherwise the prescaler is
+    /// rounded to the nearest value and the delays are off by the
+    /// rounding error, e.g. about 11 % with a timer clock of 4.5 MHz.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the timer clock is below 1 MHz, as the timer can not
+    /// count in steps of one microsecond then.
+    pub fn new(timer: Timer<TIM>) -> Self {
+        timer.tim.disable();
+
+        let timer_clock = TIM::timer_clock(&timer.clocks);
+        crate::assert!(timer_clock.0 >= 1_000_000);
+        let ticks = Timeout::from(MicroSeconds(1)).ticks(timer_clock);
+        let psc = crate::unwrap!(prescaler(ticks, 1).ok());
+        timer.tim.set_prescaler(psc);
+
+        Delay { timer }
+    }
+
+    /// Releases the timer
+    pub fn free(self) -> Timer<TIM> {
+        self.timer
+    }
+
+    /// Waits for the given number of microseconds,
+    /// which must fit into the counter of the timer.
+    fn wait_us(&mut self, us: u32) {
+        let tim = &self.timer.tim;
+
+        // The update event happens after ARR + 1 ticks. A single tick
+        // would need ARR = 0, which stops the counter, so in this case
+        // the counter runs through its whole range and is polled instead.
+        let arr = if us == 1 { TIM::COUNTER_MAX } else { us - 1 };
+        tim.set_auto_reload(arr);
+
+        // Reset the counter and load the prescaler and auto-reload values
+        tim.trigger_update();
+        tim.clear_update();
+
+        tim.enable();
+        if us == 1 {
+            while tim.counter() == 0 {}
+        } else {
+            while !tim.is_update() {}
+        }
+        tim.disable();
+        tim.clear_update();
+    }
+}
+
+impl<TIM> DelayUs<u32> for Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    fn delay_us(&mut self, mut us: u32) {
+        // Split long delays into delays, which fit into the counter
+        while us > 0 {
+            let chunk = core::cmp::min(us, TIM::COUNTER_MAX);
+            self.wait_us(chunk);
+            us -= chunk;
+        }
+    }
+}
+
+impl<TIM> DelayUs<u16> for Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    fn delay_us(&mut self, us: u16) {
+        self.delay_us(u32::from(us))
+    }
+}
+
+impl<TIM> DelayUs<u8> for Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    fn delay_us(&mut self, us: u8) {
+        self.delay_us(u32::from(us))
+    }
+}
+
+impl<TIM> DelayMs<u32> for Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    fn delay_ms(&mut self, mut ms: u32) {
+        // Split long delays, so that the microseconds do not overflow
+        const MAX: u32 = u32::MAX / 1_000;
+        while ms > 0 {
+            let chunk = core::cmp::min(ms, MAX);
+            self.delay_us(chunk * 1_000);
+            ms -= chunk;
+        }
+    }
+}
+
+impl<TIM> DelayMs<u16> for Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    fn delay_ms(&mut self, ms: u16) {
+        self.delay_ms(u32::from(ms));
+    }
+}
+
+impl<TIM> DelayMs<u8> for Delay<TIM>
+where
+    TIM: private::Instance,
+{
+    fn delay_ms(&mut self, ms: u8) {
+        self.delay_ms(u32::from(ms));
+    }
+}
+
+mod private {
+    use super::PclkSrc;
+    use crate::rcc::Clocks;
+    use crate::time::Hertz;
+
+    /// Timer methods private to this module
+    pub trait Instance: PclkSrc {
+        /// Maximum value of the counter
+        const COUNTER_MAX: u32;
+
+        /// Returns the frequency of the timer clock
+        fn timer_clock(clocks: &Clocks) -> Hertz;
+
+        /// Sets the prescaler (PSC)
+        fn set_prescaler(&self, psc: u16);
+
+        /// Sets the auto-reload value (ARR)
+        fn set_auto_reload(&self, arr: u32);
+
+        /// Triggers an update event (UG bit), which resets the counter
+        fn trigger_update(&self);
+
+        /// Returns true, if an update event happened (UIF bit)
+        fn is_update(&self) -> bool;
+
+        /// Clears the update interrupt flag (UIF bit)
+        fn clear_update(&self);
+
+        /// Returns the value of the counter
+        fn counter(&self) -> u32;
+
+        /// Starts the counter
+        fn enable(&self);
+
+        /// Stops the counter
+        fn disable(&self);
+    }
+}
+
+// Frequency of the timer clock
+macro_rules! timclk {
+    (pclk1, $clocks:expr) => {
+        $clocks.timclk1()
+    };
+    (pclk2, $clocks:expr) => {
+        $clocks.timclk2()
+    };
+}
+
+// Maximum value of the counter of the timer
+macro_rules! counter_max {
+    (TIM2) => {
+        u32::MAX
+    };
+    (TIM5) => {
+        u32::MAX
+    };
+    ($TIMX:ident) => {
+        u16::MAX as u32
+    };
+}
+
 macro_rules! hal {
     ($({
         $TIMX:ident: ($tim:ident, $timXen:ident, $timXrst:ident),
@@ -89,39 +491,19 @@ macro_rules! hal {
             impl Periodic for Timer<$TIMX> {}
 
             impl CountDown for Timer<$TIMX> {
-                type Time = Hertz;
+                type Time = Timeout;
 
+                /// Starts a new count down
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the timeout can not be reached with this timer.
+                /// Use [`try_start`](Timer::try_start) to handle this case.
                 fn start<T>(&mut self, timeout: T)
                 where
-                    T: Into<Hertz>,
+                    T: Into<Timeout>,
                 {
-                    self.stop();
-
-                    let frequency = timeout.into().0;
-                    let timer_clock = $TIMX::get_clk(&self.clocks);
-                    let ticks = timer_clock.0 * if self.clocks.ppre1() == 1 { 1 } else { 2 }
-                        / frequency;
-                    let psc = crate::unwrap!(u16::try_from((ticks - 1) / (1 << 16)).ok());
-
-                    // NOTE(write): uses all bits in this register.
-                    self.tim.psc.write(|w| w.psc().bits(psc));
-
-                    let arr = crate::unwrap!(u16::try_from(ticks / u32::from(psc + 1)).ok());
-
-                    // TODO (sh3rm4n)
-                    // self.tim.arr.write(|w| { w.arr().bits(arr) });
-                    self.tim.arr.write(|w| unsafe { w.bits(u32::from(arr)) });
-
-                    // Trigger an update event to load the prescaler value to the clock
-                    // NOTE(write): uses all bits in this register.
-                    self.tim.egr.write(|w| w.ug().update());
-                    // The above line raises an update event which will indicate
-                    // that the timer is already finished. Since this is not the case,
-                    // it should be cleared
-                    self.clear_update_interrupt_flag();
-
-                    // start counter
-                    self.tim.cr1.modify(|_, w| w.cen().enabled());
+                    crate::expect!(self.try_start(timeout).ok(), "Unreachable timeout");
                 }
 
                 fn wait(&mut self) -> nb::Result<(), Void> {
@@ -136,9 +518,13 @@ macro_rules! hal {
 
             impl Timer<$TIMX> {
                 /// Configures a TIM peripheral as a periodic count down timer
+                ///
+                /// # Panics
+                ///
+                /// Panics, if the timeout can not be reached with this timer.
                 pub fn $tim<T>(tim: $TIMX, timeout: T, clocks: Clocks, $apb: &mut $APB) -> Self
                 where
-                    T: Into<Hertz>,
+                    T: Into<Timeout>,
                 {
                     // enable and reset peripheral to a clean slate state
                     $apb.enr().modify(|_, w| w.$timXen().enabled());
@@ -151,6 +537,40 @@ macro_rules! hal {
                     timer
                 }
 
+                /// Starts a new count down
+                ///
+                /// Returns an error and leaves the timer stopped, if the timeout
+                /// can not be reached with this timer.
+                pub fn try_start<T>(&mut self, timeout: T) -> Result<(), Error>
+                where
+                    T: Into<Timeout>,
+                {
+                    self.stop();
+
+                    let ticks = timeout.into().ticks(timclk!($pclkX, self.clocks));
+                    let (psc, arr) = prescaler_and_auto_reload(ticks, counter_max!($TIMX))?;
+
+                    // NOTE(write): uses all bits in this register.
+                    self.tim.psc.write(|w| w.psc().bits(psc));
+
+                    // TODO (sh3rm4n)
+                    // self.tim.arr.write(|w| { w.arr().bits(arr) });
+                    self.tim.arr.write(|w| unsafe { w.bits(arr) });
+
+                    // Trigger an update event to load the prescaler value to the clock
+                    // NOTE(write): uses all bits in this register.
+                    self.tim.egr.write(|w| w.ug().update());
+                    // The above line raises an update event which will indicate
+                    // that the timer is already finished. Since this is not the case,
+                    // it should be cleared
+                    self.clear_update_interrupt_flag();
+
+                    // start counter
+                    self.tim.cr1.modify(|_, w| w.cen().enabled());
+
+                    Ok(())
+                }
+
                 /// Starts listening for an `event`
                 pub fn listen(&mut self, event: Event) {
                     match event {
@@ -181,6 +601,49 @@ macro_rules! hal {
                     self.tim
                 }
             }
+
+            impl private::Instance for $TIMX {
+                const COUNTER_MAX: u32 = counter_max!($TIMX);
+
+                fn timer_clock(clocks: &Clocks) -> Hertz {
+                    timclk!($pclkX, clocks)
+                }
+
+                fn set_prescaler(&self, psc: u16) {
+                    // NOTE(write): uses all bits in this register.
+                    self.psc.write(|w| w.psc().bits(psc));
+                }
+
+                fn set_auto_reload(&self, arr: u32) {
+                    // NOTE(write): uses all bits in this register.
+                    self.arr.write(|w| unsafe { w.bits(arr) });
+                }
+
+                fn trigger_update(&self) {
+                    // NOTE(write): uses all bits in this register.
+                    self.egr.write(|w| w.ug().update());
+                }
+
+                fn is_update(&self) -> bool {
+                    !self.sr.read().uif().is_clear()
+                }
+
+                fn clear_update(&self) {
+                    self.sr.modify(|_, w| w.uif().clear());
+                }
+
+                fn counter(&self) -> u32 {
+                    self.cnt.read().bits()
+                }
+
+                fn enable(&self) {
+                    self.cr1.modify(|_, w| w.cen().enabled());
+                }
+
+                fn disable(&self) {
+                    self.cr1.modify(|_, w| w.cen().disabled());
+                }
+            }
         )+
     }
 }
@@ -432,3 +895,381 @@ hal! {
         APB2: (apb2, pclk2),
     },
 }
+
+macro_rules! master {
+    ($($TIMX:ident),+) => {
+        $(
+            impl Timer<$TIMX> {
+                /// Selects the source of the trigger output (TRGO) of this timer,
+                /// which can be used by slave timers
+                pub fn set_master_mode(&mut self, mode: MasterMode) {
+                    // NOTE(unsafe): Only the MMS bits are modified.
+                    self.tim
+                        .cr2
+                        .modify(|r, w| unsafe { w.bits((r.bits() & !(0b111 << 4)) | mode.bits() << 4) });
+                }
+            }
+        )+
+    }
+}
+
+macro_rules! slave {
+    ($($TIMX:ident),+) => {
+        $(
+            impl Timer<$TIMX> {
+                /// Makes this timer a slave of the `MASTER` timer, which reacts
+                /// on the trigger output of the master timer
+                ///
+                /// ```
+                /// // Count the update events of TIM3 with TIM2
+                /// tim3.set_master_mode(MasterMode::Update);
+                /// tim2.set_slave_mode::<TIM3>(SlaveMode::ExternalClock);
+                /// ```
+                pub fn set_slave_mode<MASTER>(&mut self, mode: SlaveMode)
+                where
+                    MASTER: InternalTrigger<$TIMX>,
+                {
+                    let ts = u32::from(MASTER::ITR);
+                    // NOTE(unsafe): Only the SMS and TS bits are modified.
+                    self.tim.smcr.modify(|r, w| unsafe {
+                        w.bits((r.bits() & !(1 << 16 | 0b111 << 4 | 0b111)) | ts << 4 | mode.bits())
+                    });
+                }
+
+                /// Disables the slave mode of this timer
+                pub fn disable_slave_mode(&mut self) {
+                    // NOTE(unsafe): Only the SMS bits are modified.
+                    self.tim
+                        .smcr
+                        .modify(|r, w| unsafe { w.bits(r.bits() & !(1 << 16 | 0b111)) });
+                }
+            }
+        )+
+    }
+}
+
+macro_rules! internal_trigger {
+    ($SLAVE:ident: [$($(#[$attr:meta])* $MASTER:ident: $itr:expr,)+]) => {
+        $(
+            $(#[$attr])*
+            unsafe impl InternalTrigger<crate::pac::$SLAVE> for crate::pac::$MASTER {
+                const ITR: u8 = $itr;
+            }
+        )+
+    };
+}
+
+master!(TIM2, TIM6, TIM15);
+slave!(TIM2, TIM15);
+
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+master!(TIM1);
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+slave!(TIM1);
+
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f398"
+))]
+master!(TIM3, TIM7);
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f334",
+    feature = "stm32f358",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f398"
+))]
+slave!(TIM3);
+
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f398"
+))]
+master!(TIM4);
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f373",
+    feature = "stm32f378",
+    feature = "stm32f398"
+))]
+slave!(TIM4);
+
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398",
+))]
+master!(TIM8);
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398",
+))]
+slave!(TIM8);
+
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398",
+))]
+master!(TIM20);
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+master!(TIM5, TIM19);
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+slave!(TIM5, TIM12, TIM19);
+
+// Internal trigger connections of the stm32f301, stm32f302, stm32f303,
+// stm32f334 and stm32f3x8 devices.
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+internal_trigger!(TIM1: [
+    TIM15: 0,
+    TIM2: 1,
+    #[cfg(not(any(feature = "stm32f301", feature = "stm32f318")))]
+    TIM3: 2,
+    #[cfg(any(
+        feature = "stm32f302",
+        feature = "stm32f303",
+        feature = "stm32f328",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    TIM4: 3,
+]);
+
+#[cfg(not(any(feature = "stm32f373", feature = "stm32f378")))]
+internal_trigger!(TIM2: [
+    TIM1: 0,
+    #[cfg(any(
+        feature = "stm32f303",
+        feature = "stm32f328",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    TIM8: 1,
+    #[cfg(not(any(feature = "stm32f301", feature = "stm32f318")))]
+    TIM3: 2,
+    #[cfg(any(
+        feature = "stm32f302",
+        feature = "stm32f303",
+        feature = "stm32f328",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    TIM4: 3,
+]);
+
+#[cfg(not(any(
+    feature = "stm32f301",
+    feature = "stm32f318",
+    feature = "stm32f373",
+    feature = "stm32f378"
+)))]
+internal_trigger!(TIM3: [
+    TIM1: 0,
+    TIM2: 1,
+    TIM15: 2,
+    #[cfg(any(
+        feature = "stm32f302",
+        feature = "stm32f303",
+        feature = "stm32f328",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    TIM4: 3,
+]);
+
+#[cfg(any(
+    feature = "stm32f302",
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+internal_trigger!(TIM4: [
+    TIM1: 0,
+    TIM2: 1,
+    TIM3: 2,
+    #[cfg(any(
+        feature = "stm32f303",
+        feature = "stm32f328",
+        feature = "stm32f358",
+        feature = "stm32f398"
+    ))]
+    TIM8: 3,
+]);
+
+#[cfg(any(
+    feature = "stm32f303",
+    feature = "stm32f328",
+    feature = "stm32f358",
+    feature = "stm32f398"
+))]
+internal_trigger!(TIM8: [
+    TIM1: 0,
+    TIM2: 1,
+    TIM4: 2,
+    TIM3: 3,
+]);
+
+// The connections of TIM15 are the same on all devices.
+internal_trigger!(TIM15: [
+    TIM2: 0,
+    #[cfg(not(any(feature = "stm32f301", feature = "stm32f318")))]
+    TIM3: 1,
+]);
+
+// Internal trigger connections of the stm32f373 and stm32f378 devices
+// (RM0313, TIMx internal trigger connection tables).
+//
+// The output compare signals of TIM13, TIM14, TIM16 and TIM17, which are
+// connected to some of the internal trigger inputs as well, are no trigger
+// outputs and left out.
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+internal_trigger!(TIM2: [
+    TIM19: 0,
+    TIM15: 1,
+    TIM3: 2,
+    TIM4: 3,
+]);
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+internal_trigger!(TIM3: [
+    TIM19: 0,
+    TIM2: 1,
+    TIM5: 2,
+    TIM4: 3,
+]);
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+internal_trigger!(TIM4: [
+    TIM19: 0,
+    TIM2: 1,
+    TIM3: 2,
+    TIM15: 3,
+]);
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+internal_trigger!(TIM5: [
+    TIM2: 0,
+    TIM3: 1,
+    TIM4: 2,
+    TIM15: 3,
+]);
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+internal_trigger!(TIM12: [
+    TIM4: 0,
+    TIM5: 1,
+]);
+
+#[cfg(any(feature = "stm32f373", feature = "stm32f378"))]
+internal_trigger!(TIM19: [
+    TIM2: 0,
+    TIM3: 1,
+    TIM15: 2,
+    TIM4: 3,
+]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_ticks() {
+        let clock = Hertz(72_000_000);
+        assert_eq!(Timeout::from(Hertz(1_000)).ticks(clock), 72_000);
+        // 72 MHz / 7 Hz = 10285714.29 ticks
+        assert_eq!(Timeout::from(Hertz(7)).ticks(clock), 10_285_714);
+        assert_eq!(Timeout::from(Hertz(0)).ticks(clock), u64::MAX);
+        assert_eq!(Timeout::from(Seconds(1)).ticks(clock), 72_000_000);
+        assert_eq!(Timeout::from(MilliSeconds(1)).ticks(clock), 72_000);
+        assert_eq!(Timeout::from(MicroSeconds(1)).ticks(Hertz(4_500_000)), 5);
+        assert_eq!(
+            Timeout::from(Seconds(u32::MAX)).ticks(clock),
+            309_237_645_240_000_000
+        );
+    }
+
+    #[test]
+    fn prescaler_and_auto_reload_16_bit() {
+        let max = u32::from(u16::MAX);
+        assert_eq!(prescaler_and_auto_reload(2, max).unwrap(), (0, 1));
+        assert_eq!(prescaler_and_auto_reload(65_536, max).unwrap(), (0, 65_535));
+        assert_eq!(prescaler_and_auto_reload(65_537, max).unwrap(), (1, 32_768));
+        // 72 MHz / 1 Hz needs a prescaler of 1099, the period is rounded
+        assert_eq!(
+            prescaler_and_auto_reload(72_000_000, max).unwrap(),
+            (1_098, 65_513)
+        );
+        assert_eq!(
+            prescaler_and_auto_reload(65_536 * 65_536, max).unwrap(),
+            (65_535, 65_535)
+        );
+    }
+
+    #[test]
+    fn prescaler_and_auto_reload_32_bit() {
+        assert_eq!(
+            prescaler_and_auto_reload(72_000_000, u32::MAX).unwrap(),
+            (0, 71_999_999)
+        );
+        assert_eq!(
+            prescaler_and_auto_reload(1 << 32, u32::MAX).unwrap(),
+            (0, u32::MAX)
+        );
+        assert_eq!(
+            prescaler_and_auto_reload((1 << 32) + 1, u32::MAX).unwrap(),
+            (1, 1 << 31)
+        );
+    }
+
+    #[test]
+    fn prescaler_and_auto_reload_out_of_range() {
+        let max = u32::from(u16::MAX);
+        assert!(matches!(
+            prescaler_and_auto_reload(0, max),
+            Err(Error::TooShort)
+        ));
+        assert!(matches!(
+            prescaler_and_auto_reload(1, max),
+            Err(Error::TooShort)
+        ));
+        assert!(matches!(
+            prescaler_and_auto_reload(65_536 * 65_536 + 1, max),
+            Err(Error::TooLong)
+        ));
+        assert!(matches!(
+            prescaler_and_auto_reload(u64::MAX, u32::MAX),
+            Err(Error::TooLong)
+        ));
+    }
+
+    #[test]
+    fn prescaler_rounding() {
+        assert_eq!(prescaler(72, 1).unwrap(), 71);
+        // 4.5 ticks per count are rounded to 5
+        assert_eq!(prescaler(45, 10).unwrap(), 4);
+        assert_eq!(prescaler(44, 10).unwrap(), 3);
+        assert_eq!(prescaler(65_536 * 100, 100).unwrap(), 65_535);
+        assert!(matches!(prescaler(4, 10), Err(Error::TooShort)));
+        assert!(matches!(prescaler(65_537 * 100, 100), Err(Error::TooLong)));
+        assert!(matches!(prescaler(u64::MAX, 1), Err(Error::TooLong)));
+    }
+}
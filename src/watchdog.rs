@@ -7,7 +7,8 @@
 use crate::hal::watchdog::{Watchdog, WatchdogEnable};
 
 use crate::pac::{DBGMCU, IWDG};
-use crate::time::MilliSeconds;
+use crate::time::{Hertz, MilliSeconds};
+use crate::timer::Timeout;
 
 const LSI_KHZ: u32 = 40;
 const MAX_PR: u8 = 8;
@@ -93,10 +94,17 @@ impl IndependentWatchDog {
 }
 
 impl WatchdogEnable for IndependentWatchDog {
-    type Time = MilliSeconds;
+    type Time = Timeout;
 
+    /// Starts the watchdog with the given timeout
+    ///
+    /// The timeout can be given in any duration unit, e.g. `1.s()`
+    /// or `500.ms()`, and is rounded to whole milliseconds.
+    /// Timeouts above the maximum of the watchdog (about 26 s) are limited
+    /// to the maximum.
     fn start<T: Into<Self::Time>>(&mut self, period: T) {
-        self.setup(period.into().0);
+        let timeout_ms = period.into().ticks(Hertz(1_000));
+        self.setup(core::cmp::min(timeout_ms, u64::from(u32::MAX)) as u32);
 
         self.iwdg.kr.write(|w| w.key().start());
     }